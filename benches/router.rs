@@ -0,0 +1,85 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use futures::Future;
+
+use obsidian::context::Context;
+use obsidian::router::{Responder, Router};
+use obsidian::{App, Body, Request};
+
+fn handler(_ctx: Context) -> impl Responder {
+    "handler"
+}
+
+fn router() -> Router {
+    let mut router = Router::new();
+
+    // Pass-through middlewares so that the dispatch runs the chain without logging
+    router.use_fn(|ctx, executor| executor.next(ctx));
+
+    for resource in ["users", "posts", "comments", "tags", "teams", "projects"].iter() {
+        router.get(&format!("/api/v1/{}", resource), handler);
+        router.post(&format!("/api/v1/{}", resource), handler);
+        router.get(&format!("/api/v1/{}/:id", resource), handler);
+        router.put(&format!("/api/v1/{}/:id", resource), handler);
+        router.get(
+            &format!("/api/v1/{}/:id/history/:version", resource),
+            handler,
+        );
+        router.use_fn_to(&format!("/api/v1/{}", resource), |ctx, executor| {
+            executor.next(ctx)
+        });
+    }
+
+    router.get("/static/*", handler);
+
+    router
+}
+
+const PATHS: [&str; 5] = [
+    "/api/v1/users",
+    "/api/v1/projects/42",
+    "/api/v1/tags/7/history/3",
+    "/static/css/site.css",
+    "/api/v1/unknown/1",
+];
+
+fn bench_search_route(c: &mut Criterion) {
+    let router = router();
+    let matcher = router.freeze();
+
+    c.bench_function("route_trie search_route", |b| {
+        b.iter(|| {
+            for path in PATHS.iter() {
+                black_box(router.search_route(black_box(path)).is_some());
+            }
+        })
+    });
+
+    c.bench_function("route_matcher search_route", |b| {
+        b.iter(|| {
+            for path in PATHS.iter() {
+                black_box(matcher.search_route(black_box(path)).is_some());
+            }
+        })
+    });
+}
+
+fn bench_dispatch(c: &mut Criterion) {
+    let mut app = App::new();
+    app.use_router("/", router());
+    let server = app.into_server();
+
+    // Whole dispatch from the request to the response including the middleware chain
+    c.bench_function("app_server resolve_endpoint", |b| {
+        b.iter(|| {
+            for path in PATHS.iter() {
+                let req = Request::get(*path).body(Body::empty()).unwrap();
+                let res = server.resolve_endpoint(req).wait().unwrap();
+
+                black_box(res.status());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_search_route, bench_dispatch);
+criterion_main!(benches);
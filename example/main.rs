@@ -83,48 +83,24 @@ fn main() {
     app.post("/jsonteststructapi", responder_obsidian_error);
 
     app.get("/test/wildcard/*", |ctx: Context| {
-        format!(
-            "{}<br>{}",
-            "<h1>Test wildcard</h1>".to_string(),
-            ctx.uri().path()
-        )
+        format!("{}<br>{}", "<h1>Test wildcard</h1>", ctx.uri().path())
     });
 
     app.get("router/test", |ctx: Context| {
-        format!(
-            "{}<br>{}",
-            "<h1>router test get</h1>".to_string(),
-            ctx.uri().path()
-        )
+        format!("{}<br>{}", "<h1>router test get</h1>", ctx.uri().path())
     });
     app.post("router/test", |ctx: Context| {
-        format!(
-            "{}<br>{}",
-            "<h1>router test post</h1>".to_string(),
-            ctx.uri().path()
-        )
+        format!("{}<br>{}", "<h1>router test post</h1>", ctx.uri().path())
     });
     app.put("router/test", |ctx: Context| {
-        format!(
-            "{}<br>{}",
-            "<h1>router test put</h1>".to_string(),
-            ctx.uri().path()
-        )
+        format!("{}<br>{}", "<h1>router test put</h1>", ctx.uri().path())
     });
     app.delete("router/test", |ctx: Context| {
-        format!(
-            "{}<br>{}",
-            "<h1>router test delete</h1>".to_string(),
-            ctx.uri().path()
-        )
+        format!("{}<br>{}", "<h1>router test delete</h1>", ctx.uri().path())
     });
 
     app.get("route/diff_route", |ctx: Context| {
-        format!(
-            "{}<br>{}",
-            "<h1>route diff get</h1>".to_string(),
-            ctx.uri().path()
-        )
+        format!("{}<br>{}", "<h1>route diff get</h1>", ctx.uri().path())
     });
 
     let mut form_router = Router::new();
@@ -152,7 +128,7 @@ fn main() {
     });
     param_router.get("/paramtest/:id/test", |ctx: Context| {
         let mut param_test: i32 = ctx.param("id").unwrap();
-        param_test = param_test * 10;
+        param_test *= 10;
 
         dbg!(&param_test);
 
@@ -173,6 +149,13 @@ fn main() {
     app.use_router("/forms/", form_router);
     app.use_static_to("/files/", "/assets/");
 
+    app.get("/__routes", {
+        let routes = app.routes().to_string();
+        move |_ctx| routes.clone()
+    });
+
+    print!("{}", app.routes());
+
     app.listen(&addr, || {
         println!("server is listening to {}", &addr);
     });
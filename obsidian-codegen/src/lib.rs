@@ -0,0 +1,40 @@
+//! Route attribute macros for Obsidian
+//!
+//! The macros are re-exported by the `obsidian` crate, the annotated function is
+//! replaced by a unit struct which registers it through `Router::service`.
+
+extern crate proc_macro;
+
+mod route;
+
+use proc_macro::TokenStream;
+
+/// Register the function as the `GET` handler of the path
+#[proc_macro_attribute]
+pub fn get(args: TokenStream, input: TokenStream) -> TokenStream {
+    route::expand("get", args, input)
+}
+
+/// Register the function as the `POST` handler of the path
+#[proc_macro_attribute]
+pub fn post(args: TokenStream, input: TokenStream) -> TokenStream {
+    route::expand("post", args, input)
+}
+
+/// Register the function as the `PUT` handler of the path
+#[proc_macro_attribute]
+pub fn put(args: TokenStream, input: TokenStream) -> TokenStream {
+    route::expand("put", args, input)
+}
+
+/// Register the function as the `DELETE` handler of the path
+#[proc_macro_attribute]
+pub fn delete(args: TokenStream, input: TokenStream) -> TokenStream {
+    route::expand("delete", args, input)
+}
+
+/// Register the function as the `PATCH` handler of the path
+#[proc_macro_attribute]
+pub fn patch(args: TokenStream, input: TokenStream) -> TokenStream {
+    route::expand("patch", args, input)
+}
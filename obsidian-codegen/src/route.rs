@@ -0,0 +1,73 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, Error, Ident, ItemFn, LitStr, Token};
+
+/// Arguments of the route attribute, for example `("/users/:id", name = "user.show")`
+struct RouteArgs {
+    path: LitStr,
+    name: Option<LitStr>,
+}
+
+impl Parse for RouteArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path: LitStr = input.parse()?;
+        let mut name = None;
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+
+            if input.is_empty() {
+                break;
+            }
+
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+
+            if key == "name" {
+                name = Some(value);
+            } else {
+                return Err(Error::new(
+                    key.span(),
+                    format!("Unknown route option `{}`", key),
+                ));
+            }
+        }
+
+        Ok(RouteArgs { path, name })
+    }
+}
+
+pub fn expand(method: &str, args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as RouteArgs);
+    let func = parse_macro_input!(input as ItemFn);
+
+    if let Err(message) = obsidian_path::validate(&args.path.value()) {
+        return Error::new(args.path.span(), message)
+            .to_compile_error()
+            .into();
+    }
+
+    let vis = &func.vis;
+    let ident = &func.sig.ident;
+    let method = Ident::new(method, Span::call_site());
+    let path = &args.path;
+    let name = args.name.map(|name| quote!(.name(#name)));
+
+    let expanded = quote! {
+        #[allow(non_camel_case_types)]
+        #vis struct #ident;
+
+        impl ::obsidian::router::RouteService for #ident {
+            fn register(&self, router: &mut ::obsidian::router::Router) {
+                #func
+
+                router.#method(#path, #ident)#name;
+            }
+        }
+    };
+
+    expanded.into()
+}
@@ -0,0 +1,46 @@
+//! Route path rules of Obsidian
+//!
+//! The router checks the paths when the routes are registered and the route attribute
+//! macros check them at compile time, both through `validate`.
+
+/// Check the segments of the route path
+/// Error with the message describing the invalid segment
+pub fn validate(path: &str) -> Result<(), String> {
+    let segments = path
+        .split('/')
+        .filter(|key| !key.is_empty())
+        .collect::<Vec<&str>>();
+
+    for (pos, segment) in segments.iter().enumerate() {
+        if segment.contains('*') {
+            if segment.len() != 1 {
+                return Err(format!("Consisting * in route name at: {}", path));
+            } else if pos != segments.len() - 1 {
+                return Err(format!("* must be in the last at: {}", path));
+            }
+        }
+
+        if let Some(param) = segment.strip_prefix(':') {
+            if param.is_empty() {
+                return Err(format!("Missing param name in route at: {}", path));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_path_test() {
+        assert!(validate("/").is_ok());
+        assert!(validate("/users/:id/").is_ok());
+        assert!(validate("/files/*").is_ok());
+        assert!(validate("/files/*/raw").is_err());
+        assert!(validate("/files/*.txt").is_err());
+        assert!(validate("/users/:").is_err());
+    }
+}
@@ -1,15 +1,33 @@
+use std::collections::HashMap;
+use std::fmt::Display;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
 use futures::{future, Future, Stream};
-use hyper::{service::service_fn, Body, Request, Response, Server, StatusCode};
+use hyper::{
+    header,
+    http::request::Parts,
+    server::conn::AddrStream,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode, Uri,
+};
+use url::percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
 
-use crate::context::Context;
-use crate::middleware::Middleware;
-use crate::router::{Handler, Router};
+use crate::context::{Context, RemoteAddr, RoutePattern};
+use crate::instrument;
+use crate::middleware::{Hook, Middleware};
+use crate::router::{
+    is_normalized_path, normalize_path, Handler, HostPattern, Mount, ResourceBuilder,
+    ResourceController, RewriteRule, RouteBuilder, RouteChain, RouteMatcher, RouteNames,
+    RouteService, RouteTable, Router, Rules, TrailingSlash, Versioning,
+};
+use crate::ObsidianError;
 
 pub struct App {
     router: Router,
+    hosts: Vec<(HostPattern, Router)>,
+    rewrites: Vec<RewriteRule>,
+    pre_routing: Vec<Arc<dyn Middleware>>,
 }
 
 impl Default for App {
@@ -22,23 +40,49 @@ impl App {
     pub fn new() -> Self {
         App {
             router: Router::new(),
+            hosts: Vec::default(),
+            rewrites: Vec::default(),
+            pre_routing: Vec::default(),
         }
     }
 
-    pub fn get(&mut self, path: &str, handler: impl Handler) {
-        self.router.get(path, handler);
+    pub fn get(&mut self, path: &str, handler: impl Handler) -> RouteBuilder<'_> {
+        self.router.get(path, handler)
     }
 
-    pub fn post(&mut self, path: &str, handler: impl Handler) {
-        self.router.post(path, handler);
+    pub fn post(&mut self, path: &str, handler: impl Handler) -> RouteBuilder<'_> {
+        self.router.post(path, handler)
     }
 
-    pub fn put(&mut self, path: &str, handler: impl Handler) {
-        self.router.put(path, handler);
+    pub fn put(&mut self, path: &str, handler: impl Handler) -> RouteBuilder<'_> {
+        self.router.put(path, handler)
     }
 
-    pub fn delete(&mut self, path: &str, handler: impl Handler) {
-        self.router.delete(path, handler);
+    pub fn delete(&mut self, path: &str, handler: impl Handler) -> RouteBuilder<'_> {
+        self.router.delete(path, handler)
+    }
+
+    pub fn patch(&mut self, path: &str, handler: impl Handler) -> RouteBuilder<'_> {
+        self.router.patch(path, handler)
+    }
+
+    /// Register the routes declared by the route attributes
+    pub fn service(&mut self, service: impl RouteService) {
+        self.router.service(service);
+    }
+
+    /// Register the REST routes of the controller under the path by the actions of the builder
+    pub fn resource<C: ResourceController>(
+        &mut self,
+        path: &str,
+        controller: C,
+    ) -> ResourceBuilder<'_, C> {
+        self.router.resource(path, controller)
+    }
+
+    /// Set the policy for request paths which differ from the route only by a trailing slash
+    pub fn trailing_slash(&mut self, policy: TrailingSlash) {
+        self.router.trailing_slash(policy);
     }
 
     /// Apply middleware in the provided route
@@ -51,11 +95,101 @@ impl App {
         self.router.use_service(middleware);
     }
 
+    /// Apply the closure as middleware in the provided route
+    pub fn use_fn_to<F, R>(&mut self, path: &str, f: F)
+    where
+        F: Fn(Context, EndpointExecutor<'_>) -> R + Send + Sync + 'static,
+        R: Future<Item = Response<Body>, Error = hyper::Error> + Send + 'static,
+    {
+        self.router.use_fn_to(path, f);
+    }
+
+    /// Apply the closure as middleware in current relative route
+    /// The closure receives the context and the executor of the rest of the chain
+    pub fn use_fn<F, R>(&mut self, f: F)
+    where
+        F: Fn(Context, EndpointExecutor<'_>) -> R + Send + Sync + 'static,
+        R: Future<Item = Response<Body>, Error = hyper::Error> + Send + 'static,
+    {
+        self.router.use_fn(f);
+    }
+
+    /// Apply the hook in the provided route
+    pub fn use_hook_to(&mut self, path: &str, hook: impl Hook) {
+        self.router.use_hook_to(path, hook);
+    }
+
+    /// Apply the hook in current relative route
+    pub fn use_hook(&mut self, hook: impl Hook) {
+        self.router.use_hook(hook);
+    }
+
+    /// Apply middleware to every request before the route is searched
+    /// The middleware may change the method or uri used for routing and also sees unmatched requests
+    pub fn use_pre_routing(&mut self, middleware: impl Middleware) {
+        self.pre_routing.push(Arc::new(middleware));
+    }
+
     /// Apply route handler in current relative route
     pub fn use_router(&mut self, path: &str, router: Router) {
         self.router.use_router(path, router);
     }
 
+    /// Group routes and middlewares under the shared path prefix
+    pub fn scope(&mut self, prefix: &str, f: impl FnOnce(&mut Router)) {
+        self.router.scope(prefix, f);
+    }
+
+    /// Register the routes of the API version selected by the versioning strategy
+    pub fn version(&mut self, versioning: &Versioning, version: u32, f: impl FnOnce(&mut Router)) {
+        self.router.version(versioning, version, f);
+    }
+
+    /// Forward every request under the path to the hyper service with the path stripped
+    pub fn mount_service<S>(&mut self, path: &str, service: S)
+    where
+        S: hyper::service::Service<ReqBody = Body, ResBody = Body> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+    {
+        self.router.mount_service(path, service);
+    }
+
+    /// Forward every request under the path to the function with the path stripped
+    pub fn mount_fn<F, R>(&mut self, path: &str, f: F)
+    where
+        F: Fn(Request<Body>) -> R + Send + Sync + 'static,
+        R: Future<Item = Response<Body>, Error = hyper::Error> + Send + 'static,
+    {
+        self.router.mount_fn(path, f);
+    }
+
+    /// Redirect the requests of the source pattern to the target filled with the captured params
+    pub fn redirect(&mut self, from: &str, to: &str, status: StatusCode) {
+        self.router.redirect(from, to, status);
+    }
+
+    /// Rewrite the request path before the route is searched
+    /// Rules are tried in the registration order and only the first matching rule applies
+    pub fn rewrite(&mut self, from: &str, to: &str) {
+        self.rewrites.push(RewriteRule::new(from, to));
+    }
+
+    /// Register the redirects and rewrites of the rules, for example loaded by `Rules::from_file`
+    pub fn rules(&mut self, rules: Rules) {
+        for (from, redirect) in rules.redirects {
+            self.router.insert_redirect(&from, redirect);
+        }
+
+        self.rewrites.extend(rules.rewrites);
+    }
+
+    /// Route the requests of the matching host to the router
+    /// `:name` in the pattern captures a host label as param, for example `:tenant.example.com`
+    /// Hosts are matched in the registration order, unmatched hosts fall back to the app routes
+    pub fn host(&mut self, pattern: &str, router: Router) {
+        self.hosts.push((HostPattern::new(pattern), router));
+    }
+
     /// Serve static files by the virtual path as the route and directory path as the server file path
     pub fn use_static_to(&mut self, virtual_path: &str, dir_path: &str) {
         self.router.use_static_to(virtual_path, dir_path);
@@ -66,23 +200,47 @@ impl App {
         self.router.use_static(dir_path);
     }
 
+    /// Build the path of the named route with the percent-encoded params
+    pub fn url_for<I, K, V>(&self, name: &str, params: I) -> Result<String, ObsidianError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Display,
+    {
+        self.router.url_for(name, params)
+    }
+
+    /// Describe all registered routes
+    pub fn routes(&self) -> RouteTable {
+        self.router.routes()
+    }
+
+    /// Freeze the routes into the server which resolves the requests of all connections
+    pub fn into_server(self) -> AppServer {
+        AppServer::new(self.router)
+            .with_hosts(self.hosts)
+            .with_rewrites(self.rewrites)
+            .with_pre_routing(self.pre_routing)
+    }
+
     pub fn listen(self, addr: &SocketAddr, callback: impl Fn()) {
-        let app_server = AppServer {
-            router: self.router,
-        };
+        let app_server = self.into_server();
 
-        let service = move || {
+        let service = make_service_fn(move |socket: &AddrStream| {
             let server_clone = app_server.clone();
+            let remote_addr = RemoteAddr(socket.remote_addr());
 
             service_fn(
-                move |req| -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+                move |mut req: Request<Body>| -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+                    req.extensions_mut().insert(remote_addr);
+
                     // Resolve the route endpoint
                     server_clone.resolve_endpoint(req)
                 },
             )
-        };
+        });
 
-        let server = Server::bind(&addr)
+        let server = Server::bind(addr)
             .serve(service)
             .map_err(|e| eprintln!("server error: {}", e));
 
@@ -92,58 +250,275 @@ impl App {
     }
 }
 
+/// AppServer is the frozen app shared by the connections
+///
+/// Cloning it only clones the `Arc`s of the compiled routes.
 #[derive(Clone)]
-struct AppServer {
-    router: Router,
+pub struct AppServer {
+    matcher: Arc<RouteMatcher>,
+    route_names: Arc<RouteNames>,
+    hosts: Arc<Vec<VirtualHost>>,
+    rewrites: Arc<Vec<RewriteRule>>,
+    pre_routing: Arc<Vec<Arc<dyn Middleware>>>,
+}
+
+struct VirtualHost {
+    pattern: HostPattern,
+    matcher: RouteMatcher,
+    route_names: Arc<RouteNames>,
 }
 
 impl AppServer {
+    /// Freeze the router into the matcher shared by all connections
+    pub fn new(router: Router) -> Self {
+        AppServer {
+            matcher: Arc::new(router.freeze()),
+            route_names: Arc::new(router.route_names()),
+            hosts: Arc::default(),
+            rewrites: Arc::default(),
+            pre_routing: Arc::default(),
+        }
+    }
+
+    pub fn with_hosts(mut self, hosts: Vec<(HostPattern, Router)>) -> Self {
+        self.hosts = Arc::new(
+            hosts
+                .into_iter()
+                .map(|(pattern, router)| VirtualHost {
+                    pattern,
+                    matcher: router.freeze(),
+                    route_names: Arc::new(router.route_names()),
+                })
+                .collect(),
+        );
+        self
+    }
+
+    pub fn with_rewrites(mut self, rewrites: Vec<RewriteRule>) -> Self {
+        self.rewrites = Arc::new(rewrites);
+        self
+    }
+
+    pub fn with_pre_routing(mut self, pre_routing: Vec<Arc<dyn Middleware>>) -> Self {
+        self.pre_routing = Arc::new(pre_routing);
+        self
+    }
+
+    /// Uri with the path and query of the first matching rewrite rule
+    fn rewrite_uri(&self, uri: &Uri) -> Option<Uri> {
+        let path_and_query = self
+            .rewrites
+            .iter()
+            .find_map(|rule| rule.rewrite(uri.path(), uri.query()))?;
+
+        let mut uri_parts = uri.clone().into_parts();
+        uri_parts.path_and_query = Some(path_and_query.parse().ok()?);
+
+        Uri::from_parts(uri_parts).ok()
+    }
+
+    /// Select the matcher by the absolute uri authority or the host header
+    /// Return the default matcher if no host pattern matches
+    fn select_matcher(
+        &self,
+        parts: &Parts,
+    ) -> (&RouteMatcher, &Arc<RouteNames>, HashMap<String, String>) {
+        let host = parts.uri.host().or_else(|| {
+            parts
+                .headers
+                .get(header::HOST)
+                .and_then(|host| host.to_str().ok())
+        });
+
+        if let Some(host) = host {
+            for virtual_host in self.hosts.iter() {
+                if let Some(params) = virtual_host.pattern.matches(host) {
+                    return (&virtual_host.matcher, &virtual_host.route_names, params);
+                }
+            }
+        }
+
+        (&self.matcher, &self.route_names, HashMap::default())
+    }
+
     pub fn resolve_endpoint(
         &self,
         req: Request<Body>,
     ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        // Request span covers the pre-routing middlewares as well
+        instrument::request(req, |req| self.pre_route(req))
+    }
+
+    /// Run the pre-routing middlewares with the routing as their endpoint
+    fn pre_route(
+        &self,
+        req: Request<Body>,
+    ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        if self.pre_routing.is_empty() {
+            return self.route(req);
+        }
+
+        let server = self.clone();
+
+        // Pre-routing middlewares see the request before the route is known, so the body is read first
         let (parts, body) = req.into_parts();
 
-        // Currently support only one router until radix tree complete.
-        if let Some(path) = self.router.search_route(parts.uri.path()) {
-            // Temporary used as the hyper stream thread block. async will be used soon
-            Box::new(body.concat2().and_then(move |b| {
-                let route = match path.get_route(&parts.method) {
-                    Some(r) => r,
-                    None => return page_not_found(),
-                };
-                let middlewares = path.get_middlewares();
-                let params = path.get_params();
-                let req = Request::from_parts(parts, Body::from(b));
-                let context = Context::new(req, params);
-
-                let executor = EndpointExecutor::new(&route.handler, middlewares);
-
-                executor.next(context)
-            }))
-        } else {
-            page_not_found()
+        Box::new(body.concat2().and_then(move |b| {
+            let req = Request::from_parts(parts, Body::from(b));
+            let mut context = Context::new(req, HashMap::default());
+            context.set_route_names(server.route_names.clone());
+
+            let pre_routing = server.pre_routing.clone();
+            let endpoint = Endpoint::Routing(Arc::new(move |ctx: Context| {
+                server.route(ctx.into_request())
+            }));
+
+            EndpointExecutor::new(&endpoint, &pre_routing).next(context)
+        }))
+    }
+
+    /// Search the route of the request and run its middlewares and handler
+    fn route(
+        &self,
+        req: Request<Body>,
+    ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        let (mut parts, body) = req.into_parts();
+
+        // Reject paths which are not decodable or escape the root
+        if !is_normalized_path(parts.uri.path()) && normalize_path(parts.uri.path()).is_err() {
+            return bad_request();
+        }
+
+        if let Some(uri) = self.rewrite_uri(&parts.uri) {
+            parts.uri = uri;
+        }
+
+        let (matcher, route_names, host_params) = self.select_matcher(&parts);
+
+        let path = match matcher.search_route(parts.uri.path()) {
+            Some(path) => path,
+            None => return page_not_found(),
+        };
+
+        if let Some(status) = path.get_redirect(&parts.method) {
+            return redirect_trailing_slash(&parts.uri, status);
         }
+
+        let route = match path.select_route(&parts) {
+            Ok(route) => route,
+            Err(status) => return status_response(status),
+        };
+
+        let endpoint = route.endpoint.clone();
+        let RouteChain {
+            middlewares,
+            pattern,
+        } = route
+            .chain
+            .clone()
+            .expect("ERROR: Matched route is not frozen");
+        // Path params take precedence over the host params
+        let mut params = host_params;
+        params.extend(
+            path.params()
+                .map(|(key, val)| (key.to_string(), val.to_string())),
+        );
+        let route_names = route_names.clone();
+
+        let pattern = RoutePattern(pattern);
+
+        instrument::record_route(&parts.extensions, &route.path, &params);
+        parts.extensions.insert(pattern.clone());
+
+        // Temporary used as the hyper stream thread block. async will be used soon
+        Box::new(body.concat2().and_then(move |b| {
+            let req = Request::from_parts(parts, Body::from(b));
+            let mut context = Context::new(req, params);
+            context.set_route_names(route_names);
+
+            let executor = EndpointExecutor::new(&endpoint, &middlewares);
+
+            // Expose the route pattern to the pre-routing middlewares
+            executor.next(context).map(move |mut res| {
+                res.extensions_mut().insert(pattern);
+                res
+            })
+        }))
     }
 }
 
 fn page_not_found() -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
-    let mut server_response = Response::new(Body::from("404 Not Found"));
-    *server_response.status_mut() = StatusCode::NOT_FOUND;
+    status_response(StatusCode::NOT_FOUND)
+}
+
+fn status_response(
+    status: StatusCode,
+) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+    let reason = status.canonical_reason().unwrap_or_default();
+    let mut server_response = Response::new(Body::from(format!("{} {}", status.as_u16(), reason)));
+    *server_response.status_mut() = status;
+
+    Box::new(future::ok(server_response))
+}
+
+fn redirect_trailing_slash(
+    uri: &Uri,
+    status: StatusCode,
+) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+    // Built from the normalized segments so that the location never starts with `//`
+    let segments = normalize_path(uri.path()).unwrap_or_default();
+    let mut location = segments
+        .iter()
+        .map(|segment| format!("/{}", utf8_percent_encode(segment, PATH_SEGMENT_ENCODE_SET)))
+        .collect::<String>();
+
+    if !uri.path().ends_with('/') || location.is_empty() {
+        location.push('/');
+    }
+
+    if let Some(query) = uri.query() {
+        location.push('?');
+        location.push_str(query);
+    }
+
+    let server_response = Response::builder()
+        .status(status)
+        .header(header::LOCATION, location)
+        .body(Body::empty())
+        .unwrap();
+
+    Box::new(future::ok(server_response))
+}
+
+fn bad_request() -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+    let mut server_response = Response::new(Body::from("400 Bad Request"));
+    *server_response.status_mut() = StatusCode::BAD_REQUEST;
 
     Box::new(future::ok(server_response))
 }
 
+type RoutingFn = dyn Fn(Context) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send>
+    + Send
+    + Sync;
+
+/// Endpoint run by the executor after the middlewares
+#[derive(Clone)]
+pub enum Endpoint {
+    /// Handler of the matched route
+    Handler(Arc<dyn Handler>),
+    /// Service mounted under the matched route
+    Mount(Arc<Mount>),
+    /// Routing of the request, the endpoint of the pre-routing middlewares
+    Routing(Arc<RoutingFn>),
+}
+
 pub struct EndpointExecutor<'a> {
-    pub route_endpoint: &'a Arc<dyn Handler>,
+    pub route_endpoint: &'a Endpoint,
     pub middleware: &'a [Arc<dyn Middleware>],
 }
 
 impl<'a> EndpointExecutor<'a> {
-    pub fn new(
-        route_endpoint: &'a Arc<dyn Handler>,
-        middleware: &'a [Arc<dyn Middleware>],
-    ) -> Self {
+    pub fn new(route_endpoint: &'a Endpoint, middleware: &'a [Arc<dyn Middleware>]) -> Self {
         EndpointExecutor {
             route_endpoint,
             middleware,
@@ -156,20 +531,23 @@ impl<'a> EndpointExecutor<'a> {
     ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
         if let Some((current, all_next)) = self.middleware.split_first() {
             self.middleware = all_next;
-            current.handle(context, self)
+            instrument::middleware(current.name(), || current.handle(context, self))
         } else {
-            let route_response = self.route_endpoint.call(context);
-            match route_response {
-                Ok(res) => Box::new(future::ok(res)),
-                Err(err) => {
-                    let body = Body::from(std::error::Error::description(&err).to_string());
-                    let response = Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .body(body)
-                        .unwrap();
-
-                    Box::new(future::ok(response))
-                }
+            match self.route_endpoint {
+                Endpoint::Handler(handler) => instrument::handler(|| match handler.call(context) {
+                    Ok(res) => Box::new(future::ok(res)),
+                    Err(err) => {
+                        let body = Body::from(err.to_string());
+                        let response = Response::builder()
+                            .status(StatusCode::INTERNAL_SERVER_ERROR)
+                            .body(body)
+                            .unwrap();
+
+                        Box::new(future::ok(response))
+                    }
+                }),
+                Endpoint::Mount(mount) => instrument::handler(|| mount.call(context)),
+                Endpoint::Routing(route) => route(context),
             }
         }
     }
@@ -178,8 +556,23 @@ impl<'a> EndpointExecutor<'a> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::router::{guard, Responder, ResponseResult, VersionStrategy};
     use futures::Stream;
-    use hyper::StatusCode;
+    use hyper::{http::response, Method, StatusCode};
+
+    /// Send the request to the app server, the response body is read as text
+    fn send(app_server: &AppServer, req: Request<Body>) -> (response::Parts, String) {
+        let (parts, body) = send_bytes(app_server, req);
+
+        (parts, String::from_utf8(body).unwrap())
+    }
+
+    fn send_bytes(app_server: &AppServer, req: Request<Body>) -> (response::Parts, Vec<u8>) {
+        let res = app_server.resolve_endpoint(req).wait().unwrap();
+        let (parts, body) = res.into_parts();
+
+        (parts, body.concat2().wait().unwrap().to_vec())
+    }
 
     #[test]
     fn test_app_server_resolve_endpoint() {
@@ -201,7 +594,7 @@ mod test {
             "test_app_server"
         });
 
-        let app_server = AppServer { router };
+        let app_server = AppServer::new(router);
 
         let mut req_builder = Request::builder();
 
@@ -240,4 +633,1294 @@ mod test {
             expected_res_body.wait().unwrap()
         );
     }
+
+    #[test]
+    fn test_app_server_invalid_path() {
+        let mut router = Router::new();
+
+        router.get("/files/:name", |_ctx: Context| "test_app_server");
+
+        let app_server = AppServer::new(router);
+
+        let test_cases = [
+            ("/files/%2e%2e/%2e%2e/secret", StatusCode::BAD_REQUEST),
+            ("/files/%FF", StatusCode::BAD_REQUEST),
+            ("/files/a%20b", StatusCode::OK),
+            ("/files/a/b", StatusCode::NOT_FOUND),
+        ];
+
+        for case in test_cases.iter() {
+            let req = Request::builder().uri(case.0).body(Body::empty()).unwrap();
+            let (parts, _) = send(&app_server, req);
+
+            assert_eq!(parts.status, case.1);
+        }
+    }
+
+    #[test]
+    fn test_app_server_trailing_slash_redirect() {
+        let mut router = Router::new();
+
+        router.get("/users", |_ctx: Context| "test_app_server");
+        router.get("/posts/", |_ctx: Context| "test_app_server");
+        router.trailing_slash(TrailingSlash::Redirect(StatusCode::PERMANENT_REDIRECT));
+
+        let app_server = AppServer::new(router);
+
+        let test_cases = [
+            ("/users", StatusCode::OK, None),
+            (
+                "/users/?page=2",
+                StatusCode::PERMANENT_REDIRECT,
+                Some("/users?page=2"),
+            ),
+            ("/posts", StatusCode::PERMANENT_REDIRECT, Some("/posts/")),
+            ("/posts/", StatusCode::OK, None),
+        ];
+
+        for case in test_cases.iter() {
+            let req = Request::builder().uri(case.0).body(Body::empty()).unwrap();
+            let (parts, _) = send(&app_server, req);
+
+            assert_eq!(parts.status, case.1);
+            assert_eq!(
+                parts
+                    .headers
+                    .get(header::LOCATION)
+                    .map(|location| location.to_str().unwrap()),
+                case.2
+            );
+        }
+
+        let mut router = Router::new();
+
+        router.get("/:page", |_ctx: Context| "test_app_server");
+        router.trailing_slash(TrailingSlash::Redirect(StatusCode::MOVED_PERMANENTLY));
+
+        let app_server = AppServer::new(router);
+
+        // Protocol-relative location would redirect to another host
+        let test_cases = [
+            ("//evil.com/", "/evil.com"),
+            ("///evil.com/?a=1", "/evil.com?a=1"),
+            ("/John%20Doe/", "/John%20Doe"),
+        ];
+
+        for case in test_cases.iter() {
+            let req = Request::builder().uri(case.0).body(Body::empty()).unwrap();
+            let (parts, _) = send(&app_server, req);
+
+            assert_eq!(parts.status, StatusCode::MOVED_PERMANENTLY);
+            assert_eq!(parts.headers.get(header::LOCATION).unwrap(), case.1);
+        }
+    }
+
+    #[test]
+    fn test_app_server_url_for() {
+        let mut router = Router::new();
+
+        router
+            .get("/users/:id", |ctx: Context| {
+                let id: String = ctx.param("id").unwrap();
+                ctx.url_for("user.show", vec![("id", id)]).unwrap()
+            })
+            .name("user.show");
+
+        let app_server = AppServer::new(router);
+
+        let req = Request::builder()
+            .uri("/users/John%20Doe")
+            .body(Body::empty())
+            .unwrap();
+        let (_, body) = send(&app_server, req);
+
+        assert_eq!(body, "/users/John%20Doe");
+    }
+
+    struct TestHeader;
+
+    impl Middleware for TestHeader {
+        fn handle<'a>(
+            &'a self,
+            context: Context,
+            ep_executor: EndpointExecutor<'a>,
+        ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+            Box::new(ep_executor.next(context).map(|mut res| {
+                res.headers_mut()
+                    .insert("x-test", header::HeaderValue::from_static("test"));
+                res
+            }))
+        }
+    }
+
+    #[test]
+    fn test_app_server_route_middleware() {
+        let mut router = Router::new();
+
+        router.get("/admin/users", |_ctx: Context| "test_app_server");
+        router
+            .post("/admin/users", |_ctx: Context| "test_app_server")
+            .with(TestHeader);
+        router.post("/admin/users/:id", |_ctx: Context| "test_app_server");
+
+        let app_server = AppServer::new(router);
+
+        let test_cases = [
+            (Method::GET, "/admin/users", false),
+            (Method::POST, "/admin/users", true),
+            (Method::POST, "/admin/users/1", false),
+        ];
+
+        for case in test_cases.iter() {
+            let req = Request::builder()
+                .method(case.0.clone())
+                .uri(case.1)
+                .body(Body::empty())
+                .unwrap();
+            let (parts, _) = send(&app_server, req);
+
+            assert_eq!(parts.status, StatusCode::OK);
+            assert_eq!(parts.headers.contains_key("x-test"), case.2);
+        }
+    }
+
+    #[test]
+    fn test_app_server_virtual_host() {
+        let mut router = Router::new();
+        let mut api_router = Router::new();
+        let mut tenant_router = Router::new();
+
+        router.get("/", |_ctx: Context| "default");
+        api_router.get("/", |_ctx: Context| "api");
+        tenant_router.get("/", |ctx: Context| {
+            let tenant: String = ctx.param("tenant").unwrap();
+            tenant
+        });
+
+        let app_server = AppServer::new(router).with_hosts(vec![
+            (HostPattern::new("api.example.com"), api_router),
+            (HostPattern::new(":tenant.example.com"), tenant_router),
+        ]);
+
+        let test_cases = [
+            ("/", Some("api.example.com"), "api"),
+            ("/", Some("API.example.com:8080"), "api"),
+            ("http://api.example.com/", None, "api"),
+            ("/", Some("acme.example.com"), "acme"),
+            ("/", Some("example.com"), "default"),
+            ("/", None, "default"),
+        ];
+
+        for case in test_cases.iter() {
+            let mut req_builder = Request::builder();
+            req_builder.uri(case.0);
+
+            if let Some(host) = case.1 {
+                req_builder.header(header::HOST, host);
+            }
+
+            let req = req_builder.body(Body::empty()).unwrap();
+            let (_, body) = send(&app_server, req);
+
+            assert_eq!(body, case.2);
+        }
+    }
+
+    #[test]
+    fn test_app_server_guard() {
+        let mut router = Router::new();
+
+        router
+            .get("/report", |_ctx: Context| "html")
+            .guard(guard::accept("text/html"));
+        router
+            .get("/report", |_ctx: Context| "json")
+            .guard(guard::accept("application/json"));
+        router
+            .post("/report", |_ctx: Context| "json")
+            .guard(guard::content_type("application/json"));
+        router
+            .post("/report", |_ctx: Context| "form")
+            .guard(guard::content_type("application/x-www-form-urlencoded"));
+        router
+            .get("/search", |_ctx: Context| "debug")
+            .guard(guard::query("debug"));
+        router.get("/search", |_ctx: Context| "search");
+
+        let app_server = AppServer::new(router);
+
+        let test_cases = [
+            (
+                Method::GET,
+                "/report",
+                header::ACCEPT,
+                "text/html",
+                StatusCode::OK,
+                "html",
+            ),
+            (
+                Method::GET,
+                "/report",
+                header::ACCEPT,
+                "application/json",
+                StatusCode::OK,
+                "json",
+            ),
+            (
+                Method::GET,
+                "/report",
+                header::ACCEPT,
+                "image/png",
+                StatusCode::NOT_ACCEPTABLE,
+                "406 Not Acceptable",
+            ),
+            (
+                Method::POST,
+                "/report",
+                header::CONTENT_TYPE,
+                "application/json",
+                StatusCode::OK,
+                "json",
+            ),
+            (
+                Method::POST,
+                "/report",
+                header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded",
+                StatusCode::OK,
+                "form",
+            ),
+            (
+                Method::POST,
+                "/report",
+                header::CONTENT_TYPE,
+                "text/plain",
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "415 Unsupported Media Type",
+            ),
+            (
+                Method::GET,
+                "/search?debug=1",
+                header::ACCEPT,
+                "*/*",
+                StatusCode::OK,
+                "debug",
+            ),
+            (
+                Method::GET,
+                "/search",
+                header::ACCEPT,
+                "*/*",
+                StatusCode::OK,
+                "search",
+            ),
+        ];
+
+        for case in test_cases.iter() {
+            let req = Request::builder()
+                .method(case.0.clone())
+                .uri(case.1)
+                .header(case.2.clone(), case.3)
+                .body(Body::empty())
+                .unwrap();
+
+            let (parts, body) = send(&app_server, req);
+
+            assert_eq!(parts.status, case.4);
+
+            assert_eq!(body, case.5);
+        }
+    }
+
+    #[test]
+    fn test_app_server_version() {
+        let header_versioning = Versioning::new(VersionStrategy::header("api-version"))
+            .default_version(2)
+            .deprecate(1, Some("Sat, 31 Oct 2026 23:59:59 GMT"));
+        let prefix_versioning = Versioning::new(VersionStrategy::Prefix).default_version(2);
+
+        let mut router = Router::new();
+
+        for (versioning, path) in [
+            (&header_versioning, "/header"),
+            (&prefix_versioning, "/prefix"),
+        ] {
+            router.version(versioning, 1, |r| {
+                r.get(path, |_ctx: Context| "v1");
+            });
+            router.version(versioning, 2, |r| {
+                r.get(path, |_ctx: Context| "v2");
+            });
+        }
+
+        let app_server = AppServer::new(router);
+
+        let test_cases = [
+            ("/header", None, StatusCode::OK, "v2", false),
+            ("/header", Some("1"), StatusCode::OK, "v1", true),
+            ("/header", Some("v2"), StatusCode::OK, "v2", false),
+            (
+                "/header",
+                Some("3"),
+                StatusCode::NOT_FOUND,
+                "404 Not Found",
+                false,
+            ),
+            ("/v1/prefix", None, StatusCode::OK, "v1", false),
+            ("/v2/prefix", None, StatusCode::OK, "v2", false),
+            ("/prefix", None, StatusCode::OK, "v2", false),
+        ];
+
+        for case in test_cases.iter() {
+            let mut req_builder = Request::builder();
+            req_builder.uri(case.0);
+
+            if let Some(version) = case.1 {
+                req_builder.header("api-version", version);
+            }
+
+            let req = req_builder.body(Body::empty()).unwrap();
+            let (parts, body) = send(&app_server, req);
+
+            assert_eq!(parts.status, case.2);
+            assert_eq!(parts.headers.contains_key("deprecation"), case.4);
+            assert_eq!(parts.headers.contains_key("sunset"), case.4);
+
+            assert_eq!(body, case.3);
+        }
+    }
+
+    struct UsersController;
+
+    impl ResourceController for UsersController {
+        fn index(&self, _ctx: Context) -> ResponseResult {
+            "index".respond_to()
+        }
+
+        fn show(&self, ctx: Context) -> ResponseResult {
+            let id: String = ctx.param("id").unwrap();
+            format!("show {}", id).respond_to()
+        }
+
+        fn patch(&self, ctx: Context) -> ResponseResult {
+            let id: String = ctx.param("id").unwrap();
+            format!("patch {}", id).respond_to()
+        }
+    }
+
+    #[test]
+    fn test_app_server_resource_controller() {
+        let mut router = Router::new();
+
+        router
+            .resource("/users/", UsersController)
+            .index()
+            .show()
+            .patch();
+        // Methods of the actions not implemented stay free for custom routes
+        router.delete("/users/:id", |_ctx: Context| "custom destroy");
+
+        assert_eq!(router.routes().len(), 4);
+
+        let app_server = AppServer::new(router);
+
+        let test_cases = [
+            (Method::GET, "/users", StatusCode::OK, "index"),
+            (Method::GET, "/users/1", StatusCode::OK, "show 1"),
+            (Method::PATCH, "/users/1", StatusCode::OK, "patch 1"),
+            (
+                Method::POST,
+                "/users",
+                StatusCode::NOT_FOUND,
+                "404 Not Found",
+            ),
+            (Method::DELETE, "/users/1", StatusCode::OK, "custom destroy"),
+        ];
+
+        for case in test_cases.iter() {
+            let req = Request::builder()
+                .method(case.0.clone())
+                .uri(case.1)
+                .body(Body::empty())
+                .unwrap();
+
+            let (parts, body) = send(&app_server, req);
+
+            assert_eq!(parts.status, case.2);
+
+            assert_eq!(body, case.3);
+        }
+    }
+
+    #[derive(Clone)]
+    struct EchoService;
+
+    impl hyper::service::Service for EchoService {
+        type ReqBody = Body;
+        type ResBody = Body;
+        type Error = hyper::Error;
+        type Future = Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send>;
+
+        fn call(&mut self, req: Request<Body>) -> Self::Future {
+            Box::new(
+                req.into_body()
+                    .concat2()
+                    .map(|chunk| Response::new(Body::from(chunk))),
+            )
+        }
+    }
+
+    #[test]
+    fn test_app_server_mount() {
+        let mut router = Router::new();
+
+        router.mount_fn("/legacy", |req: Request<Body>| {
+            future::ok(Response::new(Body::from(format!(
+                "{} {}",
+                req.method(),
+                req.uri()
+            ))))
+        });
+        router.mount_service("/hyper/", EchoService);
+        router.use_service_to("/legacy", TestHeader);
+
+        // Mount path includes the prefixes of the routers it is merged into
+        let echo_uri =
+            |req: Request<Body>| future::ok(Response::new(Body::from(req.uri().to_string())));
+        let mut api = Router::new();
+
+        api.mount_fn("/legacy", echo_uri);
+        router.use_router("/api/:tenant", api);
+        router.version(&Versioning::new(VersionStrategy::Prefix), 1, |r| {
+            r.mount_fn("/old", echo_uri);
+        });
+
+        // Each mount is a single route of any method
+        assert_eq!(router.routes().len(), 4);
+        assert!(router.routes().iter().all(|route| route.method == "*"));
+
+        let app_server = AppServer::new(router);
+
+        let test_cases = [
+            (Method::GET, "/legacy", "", "GET /", true),
+            (
+                Method::POST,
+                "/legacy/users/1?page=2",
+                "",
+                "POST /users/1?page=2",
+                true,
+            ),
+            (
+                Method::from_bytes(b"PURGE").unwrap(),
+                "/legacy/cache",
+                "",
+                "PURGE /cache",
+                true,
+            ),
+            (Method::PUT, "/hyper/echo", "echo body", "echo body", false),
+            (Method::GET, "/api/acme/legacy/users", "", "/users", false),
+            (Method::GET, "/v1/old/users/1", "", "/users/1", false),
+        ];
+
+        for case in test_cases.iter() {
+            let req = Request::builder()
+                .method(case.0.clone())
+                .uri(case.1)
+                .body(Body::from(case.2))
+                .unwrap();
+
+            let (parts, body) = send(&app_server, req);
+
+            assert_eq!(parts.status, StatusCode::OK);
+            assert_eq!(parts.headers.contains_key("x-test"), case.4);
+
+            assert_eq!(body, case.3);
+        }
+    }
+
+    #[test]
+    fn test_app_server_rules() {
+        let mut router = Router::new();
+
+        router.get("/articles/:id", |ctx: Context| {
+            format!(
+                "article {} {}",
+                ctx.param::<String>("id").unwrap(),
+                ctx.uri().query().unwrap_or_default()
+            )
+        });
+        router.redirect("/posts/:id", "/articles/:id", StatusCode::MOVED_PERMANENTLY);
+
+        let rules = Rules::parse("rewrite /en/* /*\nredirect 302 /old/:id https://example.com/:id")
+            .unwrap();
+
+        for (from, redirect) in rules.redirects {
+            router.insert_redirect(&from, redirect);
+        }
+
+        let app_server = AppServer::new(router).with_rewrites(rules.rewrites);
+
+        let test_cases = [
+            (
+                "/posts/1?page=2",
+                StatusCode::MOVED_PERMANENTLY,
+                "/articles/1?page=2",
+                "",
+            ),
+            ("/old/1", StatusCode::FOUND, "https://example.com/1", ""),
+            (
+                "/en/articles/2?lang=en",
+                StatusCode::OK,
+                "",
+                "article 2 lang=en",
+            ),
+            (
+                "/en/posts/3",
+                StatusCode::MOVED_PERMANENTLY,
+                "/articles/3",
+                "",
+            ),
+        ];
+
+        for case in test_cases.iter() {
+            let req = Request::builder().uri(case.0).body(Body::empty()).unwrap();
+
+            let (parts, body) = send(&app_server, req);
+
+            assert_eq!(parts.status, case.1, "path {}", case.0);
+
+            if !case.2.is_empty() {
+                assert_eq!(parts.headers[header::LOCATION], case.2);
+            }
+
+            assert_eq!(body, case.3);
+        }
+    }
+
+    #[test]
+    fn test_app_server_redirect_prefix() {
+        let mut router = Router::new();
+        let mut sub_router = Router::new();
+
+        sub_router.redirect("/old/:id", "/new/:id", StatusCode::MOVED_PERMANENTLY);
+        sub_router.redirect("/files/*", "/static/*", StatusCode::PERMANENT_REDIRECT);
+        router.use_router("/api", sub_router);
+        router.version(&Versioning::new(VersionStrategy::Prefix), 1, |r| {
+            r.redirect("/old/:id", "/new/:id", StatusCode::FOUND);
+        });
+
+        let app_server = AppServer::new(router);
+
+        let test_cases = [
+            (
+                "/api/old/1?page=2",
+                StatusCode::MOVED_PERMANENTLY,
+                "/new/1?page=2",
+            ),
+            (
+                "/api/files/css/site%20v2.css",
+                StatusCode::PERMANENT_REDIRECT,
+                "/static/css/site%20v2.css",
+            ),
+            ("/v1/old/John%20Doe", StatusCode::FOUND, "/new/John%20Doe"),
+        ];
+
+        for case in test_cases.iter() {
+            let req = Request::builder().uri(case.0).body(Body::empty()).unwrap();
+            let (parts, _) = send(&app_server, req);
+
+            assert_eq!(parts.status, case.1, "path {}", case.0);
+            assert_eq!(parts.headers[header::LOCATION], case.2);
+        }
+    }
+
+    struct MethodOverride;
+
+    impl Middleware for MethodOverride {
+        fn handle<'a>(
+            &'a self,
+            mut context: Context,
+            ep_executor: EndpointExecutor<'a>,
+        ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+            let method = context
+                .headers()
+                .get("x-http-method-override")
+                .and_then(|method| Method::from_bytes(method.as_bytes()).ok());
+
+            if let Some(method) = method {
+                *context.method_mut() = method;
+            }
+
+            if context.uri().path() == "/maintenance" {
+                return status_response(StatusCode::SERVICE_UNAVAILABLE);
+            }
+
+            if context.uri().path() == "/legacy" {
+                *context.uri_mut() = "/users?legacy=true".parse().unwrap();
+            }
+
+            ep_executor.next(context)
+        }
+    }
+
+    #[test]
+    fn test_app_server_pre_routing() {
+        let mut router = Router::new();
+
+        router.get("/users", |ctx: Context| {
+            format!("get {}", ctx.uri().query().unwrap_or_default())
+        });
+        router.delete("/users", |_ctx: Context| "delete");
+
+        let app_server = AppServer::new(router)
+            .with_pre_routing(vec![Arc::new(MethodOverride), Arc::new(TestHeader)]);
+
+        let test_cases = [
+            (
+                Method::POST,
+                "/users",
+                Some("DELETE"),
+                StatusCode::OK,
+                "delete",
+            ),
+            (
+                Method::GET,
+                "/legacy",
+                None,
+                StatusCode::OK,
+                "get legacy=true",
+            ),
+            (
+                Method::GET,
+                "/maintenance",
+                None,
+                StatusCode::SERVICE_UNAVAILABLE,
+                "503 Service Unavailable",
+            ),
+            (
+                Method::GET,
+                "/unknown",
+                None,
+                StatusCode::NOT_FOUND,
+                "404 Not Found",
+            ),
+        ];
+
+        for case in test_cases.iter() {
+            let mut req = Request::builder();
+            req.method(case.0.clone()).uri(case.1);
+
+            if let Some(method) = case.2 {
+                req.header("x-http-method-override", method);
+            }
+
+            let (parts, body) = send(&app_server, req.body(Body::empty()).unwrap());
+
+            assert_eq!(parts.status, case.3, "path {}", case.1);
+            // Unmatched requests also pass through the pre-routing middlewares
+            assert_eq!(
+                parts.headers.contains_key("x-test"),
+                case.3 != StatusCode::SERVICE_UNAVAILABLE
+            );
+
+            assert_eq!(body, case.4);
+        }
+    }
+
+    #[test]
+    fn test_app_server_fn_middleware() {
+        let mut router = Router::new();
+
+        router.get("/", |_ctx: Context| "home");
+        router.get("/admin/users", |_ctx: Context| "users");
+        router.use_fn(|ctx, next| {
+            next.next(ctx).map(|mut res| {
+                res.headers_mut()
+                    .insert("x-test", header::HeaderValue::from_static("test"));
+                res
+            })
+        });
+        router.use_fn_to("/admin", |ctx, next| {
+            if ctx.headers().contains_key(header::AUTHORIZATION) {
+                next.next(ctx)
+            } else {
+                status_response(StatusCode::UNAUTHORIZED)
+            }
+        });
+
+        let app_server = AppServer::new(router);
+
+        let test_cases = [
+            ("/", StatusCode::OK, "home"),
+            ("/admin/users", StatusCode::UNAUTHORIZED, "401 Unauthorized"),
+        ];
+
+        for case in test_cases.iter() {
+            let req = Request::builder().uri(case.0).body(Body::empty()).unwrap();
+
+            let (parts, body) = send(&app_server, req);
+
+            assert_eq!(parts.status, case.1);
+            assert!(parts.headers.contains_key("x-test"));
+
+            assert_eq!(body, case.2);
+        }
+    }
+
+    struct TestHook;
+
+    impl Hook for TestHook {
+        fn before(&self, ctx: &mut Context) {
+            ctx.headers_mut()
+                .insert("x-before", header::HeaderValue::from_static("before"));
+        }
+
+        fn after(&self, info: &crate::middleware::RequestInfo, res: &mut Response<Body>) {
+            assert_eq!(info.headers["x-before"], "before");
+
+            if res.status() == StatusCode::NO_CONTENT && info.method == Method::DELETE {
+                *res.status_mut() = StatusCode::OK;
+            }
+
+            res.headers_mut().insert(
+                "x-test",
+                header::HeaderValue::from_str(info.uri.path()).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_app_server_hook() {
+        let mut router = Router::new();
+
+        router.get("/users", |ctx: Context| {
+            ctx.headers()["x-before"].to_str().unwrap().to_string()
+        });
+        router.delete("/users", |_ctx: Context| StatusCode::NO_CONTENT);
+        router.use_hook(TestHook);
+
+        let app_server = AppServer::new(router);
+
+        let test_cases = [(Method::GET, "before"), (Method::DELETE, "")];
+
+        for case in test_cases.iter() {
+            let req = Request::builder()
+                .method(case.0.clone())
+                .uri("/users")
+                .body(Body::empty())
+                .unwrap();
+
+            let (parts, body) = send(&app_server, req);
+
+            assert_eq!(parts.status, StatusCode::OK);
+            assert_eq!(parts.headers["x-test"], "/users");
+
+            assert_eq!(body, case.1);
+        }
+    }
+
+    /// Subscriber collecting the span names and the recorded fields
+    #[cfg(feature = "tracing")]
+    #[derive(Clone, Default)]
+    struct TestSubscriber {
+        records: Arc<std::sync::Mutex<Vec<String>>>,
+        spans: Arc<std::sync::Mutex<Vec<&'static tracing::Metadata<'static>>>>,
+        entered: Arc<std::sync::Mutex<Vec<tracing::span::Id>>>,
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::field::Visit for TestSubscriber {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            let record = format!("{}={:?}", field.name(), value);
+            self.records.lock().unwrap().push(record);
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for TestSubscriber {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(span.metadata());
+            let id = tracing::span::Id::from_u64(spans.len() as u64);
+            drop(spans);
+
+            let name = span.metadata().name().to_string();
+            let parent = if span.is_contextual() {
+                self.entered.lock().unwrap().last().cloned()
+            } else {
+                span.parent().cloned()
+            };
+
+            let mut records = self.records.lock().unwrap();
+            records.push(name.clone());
+
+            if let Some(parent) = parent {
+                let parent = self.spans.lock().unwrap()[parent.into_u64() as usize - 1].name();
+                records.push(format!("{} > {}", parent, name));
+            }
+
+            drop(records);
+            span.record(&mut self.clone());
+
+            id
+        }
+
+        fn record(&self, _span: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+            values.record(&mut self.clone());
+        }
+
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+        fn event(&self, _event: &tracing::Event<'_>) {}
+
+        fn enter(&self, span: &tracing::span::Id) {
+            self.entered.lock().unwrap().push(span.clone());
+        }
+
+        fn exit(&self, _span: &tracing::span::Id) {
+            self.entered.lock().unwrap().pop();
+        }
+
+        fn current_span(&self) -> tracing_core::span::Current {
+            match self.entered.lock().unwrap().last() {
+                Some(id) => {
+                    let metadata = self.spans.lock().unwrap()[id.into_u64() as usize - 1];
+                    tracing_core::span::Current::new(id.clone(), metadata)
+                }
+                None => tracing_core::span::Current::none(),
+            }
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_app_server_tracing() {
+        let mut router = Router::new();
+
+        router.get("/users/:id", |ctx: Context| {
+            assert!(!ctx.span().is_disabled());
+            "user"
+        });
+        router.use_service(TestHeader);
+
+        let app_server = AppServer::new(router);
+        let subscriber = TestSubscriber::default();
+
+        tracing::subscriber::with_default(subscriber.clone(), || {
+            let req = Request::builder()
+                .uri("/users/1")
+                .body(Body::empty())
+                .unwrap();
+
+            let (parts, _) = send(&app_server, req);
+
+            assert_eq!(parts.status, StatusCode::OK);
+        });
+
+        let records = subscriber.records.lock().unwrap();
+
+        assert_eq!(records[0], "request");
+        assert!(records.contains(&"method=GET".to_string()));
+        assert!(records.contains(&"route=\"/users/:id\"".to_string()));
+        assert!(records.contains(&"params={\"id\": \"1\"}".to_string()));
+        assert!(records.contains(&"middleware".to_string()));
+        assert!(records.contains(&"handler".to_string()));
+        assert!(records.contains(&"status=200".to_string()));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_app_server_tracing_pre_routing() {
+        let mut router = Router::new();
+
+        router.get("/users/:id", |_ctx: Context| "user");
+
+        let pre_routing = crate::middleware::FnMiddleware::new(
+            |ctx: Context, ep_executor: EndpointExecutor<'_>| {
+                assert!(!ctx.span().is_disabled());
+                ep_executor.next(ctx)
+            },
+        );
+        let app_server = AppServer::new(router).with_pre_routing(vec![Arc::new(pre_routing)]);
+        let subscriber = TestSubscriber::default();
+
+        tracing::subscriber::with_default(subscriber.clone(), || {
+            let req = Request::builder()
+                .uri("/users/1")
+                .body(Body::empty())
+                .unwrap();
+
+            let (parts, _) = send(&app_server, req);
+
+            assert_eq!(parts.status, StatusCode::OK);
+        });
+
+        let records = subscriber.records.lock().unwrap();
+
+        // Request span is the root and the pre-routing middlewares are inside it
+        assert_eq!(records[0], "request");
+        assert!(records.contains(&"request > middleware".to_string()));
+        assert!(records.contains(&"middleware > handler".to_string()));
+        assert!(!records.contains(&"middleware > request".to_string()));
+        // Routing itself is not reported as a handler
+        assert_eq!(
+            records.iter().filter(|record| *record == "handler").count(),
+            1
+        );
+        assert!(records.contains(&"route=\"/users/:id\"".to_string()));
+        assert!(records.contains(&"status=200".to_string()));
+    }
+
+    #[test]
+    fn test_app_server_metrics() {
+        let metrics = crate::middleware::Metrics::new();
+        let mut router = Router::new();
+
+        router.get("/users/:id", |ctx: Context| {
+            assert_eq!(ctx.route_pattern(), Some("/users/:id"));
+            "user"
+        });
+        router.get("/metrics", metrics.handler());
+
+        let app_server = AppServer::new(router).with_pre_routing(vec![Arc::new(metrics)]);
+
+        for path in ["/users/1", "/users/2", "/unknown", "/metrics"].iter() {
+            let req = Request::builder().uri(*path).body(Body::empty()).unwrap();
+
+            send(&app_server, req);
+        }
+
+        for method in ["FOOBAR", "BAZ"].iter() {
+            let req = Request::builder()
+                .method(*method)
+                .uri("/users/1")
+                .body(Body::empty())
+                .unwrap();
+
+            send(&app_server, req);
+        }
+
+        let req = Request::builder()
+            .uri("/metrics")
+            .body(Body::empty())
+            .unwrap();
+
+        let (parts, body) = send(&app_server, req);
+
+        assert_eq!(parts.status, StatusCode::OK);
+
+        assert!(body.contains(
+            "http_requests_total{method=\"GET\",route=\"/users/:id\",status=\"2xx\"} 2\n"
+        ));
+        assert!(body.contains(
+            "http_requests_total{method=\"GET\",route=\"unmatched\",status=\"4xx\"} 1\n"
+        ));
+        assert!(body.contains(
+            "http_requests_total{method=\"other\",route=\"unmatched\",status=\"4xx\"} 2\n"
+        ));
+        assert!(!body.contains("FOOBAR"));
+        assert!(body.contains("http_requests_in_flight 1\n"));
+    }
+
+    #[test]
+    fn test_app_server_request_id() {
+        let mut router = Router::new();
+
+        router.get("/", |ctx: Context| ctx.request_id().unwrap().to_string());
+
+        let app_server = AppServer::new(router)
+            .with_pre_routing(vec![Arc::new(crate::middleware::RequestId::new())]);
+
+        let test_cases = [
+            ("/", Some("gateway-1"), StatusCode::OK),
+            ("/", Some("invalid id"), StatusCode::OK),
+            ("/", None, StatusCode::OK),
+            ("/unknown", Some("gateway-1"), StatusCode::NOT_FOUND),
+        ];
+
+        for case in test_cases.iter() {
+            let mut req = Request::builder();
+            req.uri(case.0);
+
+            if let Some(request_id) = case.1 {
+                req.header("x-request-id", request_id);
+            }
+
+            let (parts, body) = send(&app_server, req.body(Body::empty()).unwrap());
+            let request_id = parts.headers["x-request-id"].to_str().unwrap();
+
+            assert_eq!(parts.status, case.2);
+
+            // Valid incoming ID is propagated, otherwise an UUID is generated
+            match case.1 {
+                Some("gateway-1") => assert_eq!(request_id, "gateway-1"),
+                _ => assert_eq!(request_id.len(), 36),
+            }
+
+            if case.2 == StatusCode::OK {
+                assert_eq!(body, request_id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_app_server_request_id_custom() {
+        let mut router = Router::new();
+
+        router.get("/", |ctx: Context| ctx.request_id().unwrap().to_string());
+
+        let app_server = AppServer::new(router).with_pre_routing(vec![Arc::new(
+            crate::middleware::RequestId::new()
+                .header("x-correlation-id")
+                .generator(|| "generated".to_string()),
+        )]);
+
+        let test_cases = [
+            (Some("gateway-1"), None, "gateway-1"),
+            (None, Some("gateway-1"), "generated"),
+            (None, None, "generated"),
+        ];
+
+        for case in test_cases.iter() {
+            let mut req = Request::builder();
+            req.uri("/");
+
+            if let Some(correlation_id) = case.0 {
+                req.header("x-correlation-id", correlation_id);
+            }
+
+            if let Some(request_id) = case.1 {
+                req.header("x-request-id", request_id);
+            }
+
+            let (parts, body) = send(&app_server, req.body(Body::empty()).unwrap());
+
+            assert_eq!(parts.headers["x-correlation-id"], case.2);
+            assert!(!parts.headers.contains_key("x-request-id"));
+            assert_eq!(body, case.2);
+        }
+    }
+
+    #[test]
+    fn test_app_server_trace_context() {
+        let mut router = Router::new();
+
+        router.get("/", |ctx: Context| {
+            let trace_context = ctx.trace_context().unwrap();
+
+            format!(
+                "{} {}",
+                trace_context.trace_id(),
+                trace_context.parent_id().unwrap_or_default()
+            )
+        });
+
+        let app_server = AppServer::new(router).with_pre_routing(vec![Arc::new(
+            crate::middleware::TraceContextMiddleware::new(),
+        )]);
+
+        let test_cases = [
+            (
+                "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+                Some("0af7651916cd43dd8448eb211c80319c b7ad6b7169203331"),
+            ),
+            (
+                "00-0af7651916cd43dd8448eb211c80319c-00000000000000-01",
+                None,
+            ),
+        ];
+
+        for case in test_cases.iter() {
+            let req = Request::builder()
+                .uri("/")
+                .header("traceparent", case.0)
+                .header("tracestate", "rojo=00f067aa0ba902b7")
+                .body(Body::empty())
+                .unwrap();
+
+            let (parts, body) = send(&app_server, req);
+
+            let traceparent = parts.headers["traceparent"].to_str().unwrap();
+            let tracestate = parts.headers.get("tracestate").cloned();
+
+            match case.1 {
+                Some(expected) => {
+                    assert_eq!(body, expected);
+                    assert!(traceparent.starts_with("00-0af7651916cd43dd8448eb211c80319c-"));
+                    assert_eq!(tracestate.unwrap(), "rojo=00f067aa0ba902b7");
+                }
+                // Malformed traceparent starts a new trace without the tracestate
+                None => {
+                    assert!(!traceparent.contains("0af7651916cd43dd8448eb211c80319c"));
+                    assert!(body.ends_with(' '));
+                    assert!(tracestate.is_none());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_app_server_cors() {
+        let mut router = Router::new();
+
+        router.get("/users", |_ctx: Context| "users");
+
+        let cors = crate::middleware::Cors::new()
+            .allow_origin("https://app.example.com")
+            .allow_methods(vec![Method::GET, Method::POST])
+            .allow_headers(vec!["content-type"])
+            .expose_headers(vec!["x-total-count"])
+            .allow_credentials(true)
+            .max_age(std::time::Duration::from_secs(600));
+
+        let app_server = AppServer::new(router).with_pre_routing(vec![Arc::new(cors)]);
+
+        let preflight = |origin: &str, method: &str| {
+            let req = Request::builder()
+                .method(Method::OPTIONS)
+                .uri("/users")
+                .header(header::ORIGIN, origin)
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, method)
+                .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "Content-Type")
+                .body(Body::empty())
+                .unwrap();
+
+            send(&app_server, req).0
+        };
+
+        // Preflight is answered although no OPTIONS route is registered
+        let parts = preflight("https://app.example.com", "POST");
+        let headers = &parts.headers;
+
+        assert_eq!(parts.status, StatusCode::NO_CONTENT);
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.example.com"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "GET, POST");
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+            "content-type"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+
+        assert_eq!(
+            preflight("https://app.example.com", "DELETE").status,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            preflight("https://evil.example.com", "GET").status,
+            StatusCode::FORBIDDEN
+        );
+
+        let test_cases = [
+            (Some("https://app.example.com"), true),
+            (Some("https://evil.example.com"), false),
+            (None, false),
+        ];
+
+        for case in test_cases.iter() {
+            let mut req = Request::builder();
+            req.uri("/users");
+
+            if let Some(origin) = case.0 {
+                req.header(header::ORIGIN, origin);
+            }
+
+            let (parts, _) = send(&app_server, req.body(Body::empty()).unwrap());
+            let headers = &parts.headers;
+
+            assert_eq!(parts.status, StatusCode::OK);
+            assert_eq!(headers[header::VARY], "origin");
+            assert_eq!(
+                headers.contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+                case.1
+            );
+            assert_eq!(
+                headers.contains_key(header::ACCESS_CONTROL_EXPOSE_HEADERS),
+                case.1
+            );
+        }
+    }
+
+    #[test]
+    fn test_app_server_compression() {
+        use crate::middleware::Compression;
+        use std::io::Read;
+
+        let text = "obsidian ".repeat(200);
+        let mut router = Router::new();
+
+        let body = text.clone();
+        router.get("/text", move |_ctx: Context| {
+            Response::builder()
+                .header(header::CONTENT_TYPE, "text/plain")
+                .header(header::ETAG, "\"obsidian\"")
+                .body(Body::from(body.clone()))
+        });
+        router.get("/small", |_ctx: Context| {
+            Response::builder()
+                .header(header::CONTENT_TYPE, "text/plain")
+                .body(Body::from("small"))
+        });
+        router.get("/image", |_ctx: Context| {
+            Response::builder()
+                .header(header::CONTENT_TYPE, "image/png")
+                .body(Body::from(vec![0; 2048]))
+        });
+        router.use_service(Compression::new());
+
+        let app_server = AppServer::new(router);
+
+        let request = |path: &str, accept_encoding: &str| {
+            let req = Request::builder()
+                .uri(path)
+                .header(header::ACCEPT_ENCODING, accept_encoding)
+                .body(Body::empty())
+                .unwrap();
+
+            send_bytes(&app_server, req)
+        };
+
+        let (parts, body) = request("/text", "gzip");
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(&body[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+
+        assert_eq!(parts.headers[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(parts.headers[header::VARY], "accept-encoding");
+        assert_eq!(parts.headers[header::ETAG], "W/\"obsidian\"");
+        assert!(!parts.headers.contains_key(header::CONTENT_LENGTH));
+        assert!(body.len() < text.len());
+        assert_eq!(decoded, text);
+
+        let (parts, body) = request("/text", "gzip;q=0.5, br");
+        let mut decoded = String::new();
+        brotli::Decompressor::new(&body[..], 4096)
+            .read_to_string(&mut decoded)
+            .unwrap();
+
+        assert_eq!(parts.headers[header::CONTENT_ENCODING], "br");
+        assert_eq!(decoded, text);
+
+        let (parts, body) = request("/text", "identity");
+
+        assert!(!parts.headers.contains_key(header::CONTENT_ENCODING));
+        assert_eq!(parts.headers[header::VARY], "accept-encoding");
+        assert_eq!(parts.headers[header::ETAG], "\"obsidian\"");
+        assert_eq!(body, text.as_bytes());
+
+        for path in ["/small", "/image"].iter() {
+            let (parts, _) = request(path, "gzip");
+
+            assert!(!parts.headers.contains_key(header::CONTENT_ENCODING));
+            assert!(!parts.headers.contains_key(header::VARY));
+        }
+    }
 }
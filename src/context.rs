@@ -7,9 +7,26 @@ use std::collections::HashMap;
 use std::convert::From;
 use std::str::FromStr;
 
-use crate::router::from_cow_map;
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::middleware::TraceContext;
+use crate::router::{from_cow_map, RouteNames};
 use crate::ObsidianError;
-use crate::{header::HeaderValue, Body, HeaderMap, Method, Request, Uri};
+use crate::{header::HeaderValue, Body, HeaderMap, Method, Request, Uri, Version};
+
+/// Address of the connected client stored in the request extensions
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RemoteAddr(pub(crate) SocketAddr);
+
+/// Pattern of the matched route stored in the request and response extensions
+#[derive(Clone, Debug)]
+pub(crate) struct RoutePattern(pub(crate) Arc<str>);
+
+/// Request ID stored in the request and response extensions
+#[derive(Clone, Debug)]
+pub(crate) struct RequestIdValue(pub(crate) Arc<str>);
 
 /// Context contains the data for current http connection context.
 /// For example, request information, params, method, and path.
@@ -17,16 +34,76 @@ use crate::{header::HeaderValue, Body, HeaderMap, Method, Request, Uri};
 pub struct Context {
     request: Request<Body>,
     params_data: HashMap<String, String>,
+    route_names: Arc<RouteNames>,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
 }
 
 impl Context {
     pub fn new(request: Request<Body>, params_data: HashMap<String, String>) -> Self {
         Context {
+            #[cfg(feature = "tracing")]
+            span: crate::instrument::request_span(request.extensions()),
             request,
             params_data,
+            route_names: Arc::default(),
         }
     }
 
+    pub(crate) fn set_route_names(&mut self, route_names: Arc<RouteNames>) {
+        self.route_names = route_names;
+    }
+
+    /// Address of the connected client, None if the request is not served by `App::listen`
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.request
+            .extensions()
+            .get::<RemoteAddr>()
+            .map(|remote_addr| remote_addr.0)
+    }
+
+    /// Span of the request, enter it to log inside the request from other threads
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Pattern of the matched route, for example `/users/:id`
+    /// None in the pre-routing middlewares
+    pub fn route_pattern(&self) -> Option<&str> {
+        self.request
+            .extensions()
+            .get::<RoutePattern>()
+            .map(|pattern| &*pattern.0)
+    }
+
+    /// ID of the request assigned by the `RequestId` middleware
+    pub fn request_id(&self) -> Option<&str> {
+        self.request
+            .extensions()
+            .get::<RequestIdValue>()
+            .map(|request_id| &*request_id.0)
+    }
+
+    /// Trace context of the request assigned by the `TraceContextMiddleware`
+    pub fn trace_context(&self) -> Option<&TraceContext> {
+        self.request.extensions().get::<TraceContext>()
+    }
+
+    /// Params of the matched route and host
+    pub(crate) fn params(&self) -> &HashMap<String, String> {
+        &self.params_data
+    }
+
+    pub(crate) fn extensions_mut(&mut self) -> &mut http::Extensions {
+        self.request.extensions_mut()
+    }
+
+    /// Consume the context into the request
+    pub(crate) fn into_request(self) -> Request<Body> {
+        self.request
+    }
+
     /// Access request header
     pub fn headers(&self) -> &HeaderMap<HeaderValue> {
         self.request.headers()
@@ -42,11 +119,26 @@ impl Context {
         self.request.method()
     }
 
+    /// Access mutable request method, only affects routing in the pre-routing middlewares
+    pub fn method_mut(&mut self) -> &mut Method {
+        self.request.method_mut()
+    }
+
     /// Access request uri
     pub fn uri(&self) -> &Uri {
         self.request.uri()
     }
 
+    /// Access request http version
+    pub fn version(&self) -> Version {
+        self.request.version()
+    }
+
+    /// Access mutable request uri, only affects routing in the pre-routing middlewares
+    pub fn uri_mut(&mut self) -> &mut Uri {
+        self.request.uri_mut()
+    }
+
     /// Method to get the params value according to key.
     /// Panic if key is not found.
     ///
@@ -78,6 +170,32 @@ impl Context {
             .map_err(|_err| ObsidianError::ParamError(format!("Failed to parse param {}", key)))
     }
 
+    /// Build the path of the named route with the percent-encoded params.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use obsidian::context::Context;
+    /// # use obsidian::router::{response, Responder};
+    ///
+    /// // Assumming the route "/users/:id" is named "user.show"
+    /// fn create_handler(ctx: Context) -> impl Responder {
+    ///     let location = ctx.url_for("user.show", vec![("id", 1)])?;
+    ///
+    ///     assert_eq!(location, "/users/1".to_string());
+    ///
+    ///     Ok(response::body(location))
+    /// }
+    /// ```
+    pub fn url_for<I, K, V>(&self, name: &str, params: I) -> Result<String, ObsidianError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Display,
+    {
+        self.route_names.url_for(name, params)
+    }
+
     /// Method to get the string query data from the request url.
     /// Untagged is not supported
     ///
@@ -106,13 +224,9 @@ impl Context {
     /// }
     /// ```
     pub fn uri_query<T: DeserializeOwned>(&mut self) -> Result<T, ObsidianError> {
-        let query = match self.uri().query() {
-            Some(query) => query,
-            _ => "",
-        }
-        .as_bytes();
+        let query = self.uri().query().unwrap_or_default().as_bytes();
 
-        Self::parse_queries(&query)
+        Self::parse_queries(query)
     }
 
     /// Method to get the forms query data from the request body.
@@ -158,6 +272,7 @@ impl Context {
     }
 
     /// Form value merge with Params
+    #[allow(clippy::result_unit_err)]
     pub fn form_with_param<T: DeserializeOwned>(&mut self) -> Result<T, ()> {
         unimplemented!()
     }
@@ -244,10 +359,7 @@ impl Context {
             .into_owned()
             .for_each(|(key, val)| {
                 if !val.is_empty() {
-                    parsed_form_map
-                        .entry(key)
-                        .or_insert_with(|| vec![])
-                        .push(val);
+                    parsed_form_map.entry(key).or_default().push(val);
                 }
             });
 
@@ -420,6 +532,24 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_json_with_extra_field() -> Result<(), ObsidianError> {
+        let params = HashMap::default();
+        let request = Request::new(Body::from("{\"id\":1,\"mode\":\"edit\"}"));
+
+        let mut ctx = Context::new(request, params);
+
+        let actual_result: JsonExtraResult = ctx.json()?;
+        let expected_result = JsonExtraResult {
+            id: 1,
+            mode: "edit".to_string(),
+            extra: i32::default(),
+        };
+
+        assert_eq!(actual_result, expected_result);
+        Ok(())
+    }
+
     #[test]
     fn test_json_value() -> Result<(), ObsidianError> {
         let params = HashMap::default();
@@ -9,6 +9,7 @@ use crate::router::FormError;
 #[derive(Debug)]
 pub enum ObsidianError {
     ParamError(String),
+    PathError(String),
     JsonError(JsonError),
     FormError(FormError),
     GeneralError(String),
@@ -17,22 +18,19 @@ pub enum ObsidianError {
 
 impl Display for ObsidianError {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str(std::error::Error::description(self))
-    }
-}
-
-impl std::error::Error for ObsidianError {
-    fn description(&self) -> &str {
         match *self {
-            ObsidianError::ParamError(ref msg) => msg,
-            ObsidianError::JsonError(ref err) => std::error::Error::description(err),
-            ObsidianError::FormError(ref err) => std::error::Error::description(err),
-            ObsidianError::GeneralError(ref msg) => msg,
-            ObsidianError::NoneError => "Input should not be None",
+            ObsidianError::ParamError(ref msg) => formatter.write_str(msg),
+            ObsidianError::PathError(ref msg) => formatter.write_str(msg),
+            ObsidianError::JsonError(ref err) => Display::fmt(err, formatter),
+            ObsidianError::FormError(ref err) => Display::fmt(err, formatter),
+            ObsidianError::GeneralError(ref msg) => formatter.write_str(msg),
+            ObsidianError::NoneError => formatter.write_str("Input should not be None"),
         }
     }
 }
 
+impl std::error::Error for ObsidianError {}
+
 impl From<FormError> for ObsidianError {
     fn from(error: FormError) -> Self {
         ObsidianError::FormError(error)
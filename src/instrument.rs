@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use futures::Future;
+use hyper::http::Extensions;
+
+use crate::{Body, Request, Response};
+
+type ResponseFuture = Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send>;
+
+/// Span of the request stored in the request extensions
+#[cfg(feature = "tracing")]
+#[derive(Clone, Debug)]
+pub(crate) struct RequestSpan(pub(crate) tracing::Span);
+
+/// Run the request inside the request span and record the response status
+/// The span is stored in the request extensions for the routing deeper in the middlewares
+#[cfg(feature = "tracing")]
+pub(crate) fn request(
+    mut req: Request<Body>,
+    f: impl FnOnce(Request<Body>) -> ResponseFuture,
+) -> ResponseFuture {
+    use tracing::field::Empty;
+    use tracing_futures::Instrument;
+
+    let span = tracing::info_span!(
+        "request",
+        method = %req.method(),
+        route = Empty,
+        params = Empty,
+        status = Empty
+    );
+    let status_span = span.clone();
+
+    req.extensions_mut().insert(RequestSpan(span.clone()));
+    let response = span.in_scope(|| f(req));
+
+    Box::new(
+        response
+            .map(move |res| {
+                status_span.record("status", res.status().as_u16());
+                res
+            })
+            .instrument(span),
+    )
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn request(
+    req: Request<Body>,
+    f: impl FnOnce(Request<Body>) -> ResponseFuture,
+) -> ResponseFuture {
+    f(req)
+}
+
+/// Span of the request, the current span if the request span is not in the extensions
+#[cfg(feature = "tracing")]
+pub(crate) fn request_span(extensions: &Extensions) -> tracing::Span {
+    extensions
+        .get::<RequestSpan>()
+        .map(|span| span.0.clone())
+        .unwrap_or_else(tracing::Span::current)
+}
+
+/// Record the matched route pattern and params in the request span
+#[cfg(feature = "tracing")]
+pub(crate) fn record_route(extensions: &Extensions, route: &str, params: &HashMap<String, String>) {
+    let span = request_span(extensions);
+
+    span.record("route", route);
+    span.record("params", tracing::field::debug(params));
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record_route(
+    _extensions: &Extensions,
+    _route: &str,
+    _params: &HashMap<String, String>,
+) {
+}
+
+/// Run the middleware inside its child span
+#[cfg(feature = "tracing")]
+pub(crate) fn middleware(name: &str, f: impl FnOnce() -> ResponseFuture) -> ResponseFuture {
+    use tracing_futures::Instrument;
+
+    let span = tracing::info_span!("middleware", name);
+
+    Box::new(span.in_scope(f).instrument(span))
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn middleware(_name: &str, f: impl FnOnce() -> ResponseFuture) -> ResponseFuture {
+    f()
+}
+
+/// Run the route handler inside its child span
+#[cfg(feature = "tracing")]
+pub(crate) fn handler(f: impl FnOnce() -> ResponseFuture) -> ResponseFuture {
+    use tracing_futures::Instrument;
+
+    let span = tracing::info_span!("handler");
+
+    Box::new(span.in_scope(f).instrument(span))
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn handler(f: impl FnOnce() -> ResponseFuture) -> ResponseFuture {
+    f()
+}
@@ -1,12 +1,18 @@
 //#[deny(missing_docs)]
 
+// Allow the route attribute macros to refer to `::obsidian` inside the crate
+extern crate self as obsidian;
+
 mod app;
 pub mod error;
+// Request spans of the optional tracing feature
+mod instrument;
 
 pub mod context;
 pub mod middleware;
 pub mod router;
 
-pub use app::App;
+pub use app::{App, AppServer};
 pub use error::ObsidianError;
 pub use hyper::{header, Body, HeaderMap, Method, Request, Response, StatusCode, Uri, Version};
+pub use obsidian_codegen::{delete, get, patch, post, put};
@@ -1,11 +1,30 @@
+mod compression;
+mod cors;
+mod fn_middleware;
+mod hook;
+mod log_sink;
 mod logger;
+mod metrics;
+mod request_id;
+mod trace_context;
 
 use futures::future::Future;
 
-pub use self::logger::Logger;
+pub use self::compression::{Compression, Encoding};
+pub use self::cors::Cors;
+pub use self::fn_middleware::FnMiddleware;
+pub use self::hook::{Hook, HookMiddleware, RequestInfo};
+pub use self::log_sink::{LogSink, RotatingFile};
+pub use self::logger::{LogFormat, Logger, REQUEST_ID_HEADER};
+pub use self::metrics::Metrics;
+pub use self::request_id::RequestId;
+pub use self::trace_context::{
+    TraceContext, TraceContextMiddleware, TRACEPARENT_HEADER, TRACESTATE_HEADER,
+};
 
 use crate::app::EndpointExecutor;
 use crate::context::Context;
+use crate::header::{HeaderMap, HeaderValue, VARY};
 use crate::{Body, Response};
 
 pub trait Middleware: Send + Sync + 'static {
@@ -14,4 +33,21 @@ pub trait Middleware: Send + Sync + 'static {
         context: Context,
         ep_executor: EndpointExecutor<'a>,
     ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send>;
+
+    /// Name used by the route introspection, type name by default
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Append to the vary header unless it already varies by everything
+pub(crate) fn add_vary(headers: &mut HeaderMap, vary: &'static str) {
+    let varies_by_all = headers
+        .get_all(VARY)
+        .iter()
+        .any(|value| value.to_str().is_ok_and(|value| value.trim() == "*"));
+
+    if !varies_by_all {
+        headers.append(VARY, HeaderValue::from_static(vary));
+    }
 }
@@ -0,0 +1,419 @@
+use std::io::{self, Write};
+use std::mem;
+use std::sync::Arc;
+
+use brotli::CompressorWriter;
+use flate2::write::{GzEncoder, ZlibEncoder};
+use futures::{Async, Future, Poll, Stream};
+use hyper::body::Payload;
+
+use crate::app::EndpointExecutor;
+use crate::context::Context;
+use crate::header::{
+    HeaderMap, HeaderValue, ACCEPT_ENCODING, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_LENGTH,
+    CONTENT_RANGE, CONTENT_TYPE, ETAG,
+};
+use crate::middleware::{add_vary, Middleware};
+use crate::{Body, Method, Response, StatusCode};
+
+/// Content types compressed by default, entries ending with `/` match the whole type
+const DEFAULT_CONTENT_TYPES: [&str; 7] = [
+    "text/",
+    "application/json",
+    "application/javascript",
+    "application/xml",
+    "application/wasm",
+    "image/svg+xml",
+    "application/problem+json",
+];
+
+/// Responses smaller than this are not worth compressing by default
+const DEFAULT_MIN_SIZE: u64 = 860;
+
+/// Encoding supported by the compression middleware
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    Brotli,
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    fn token(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+}
+
+/// Compression compresses the response body by the encoding accepted by the client
+///
+/// The body is compressed as it streams. Responses below the minimum size,
+/// outside the content type allowlist or already encoded are sent as they are.
+///
+/// # Example
+///
+/// ```
+/// # use obsidian::App;
+/// # use obsidian::middleware::{Compression, Encoding};
+/// let mut app = App::new();
+///
+/// app.use_service(
+///     Compression::new()
+///         .encodings(vec![Encoding::Brotli, Encoding::Gzip])
+///         .min_size(1024),
+/// );
+/// ```
+pub struct Compression {
+    encodings: Vec<Encoding>,
+    filter: Arc<ResponseFilter>,
+}
+
+/// ResponseFilter decides which responses are compressed
+#[derive(Clone)]
+struct ResponseFilter {
+    min_size: u64,
+    content_types: Vec<String>,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compression {
+    /// Brotli, gzip and deflate for the common text content types
+    pub fn new() -> Self {
+        Compression {
+            encodings: vec![Encoding::Brotli, Encoding::Gzip, Encoding::Deflate],
+            filter: Arc::new(ResponseFilter {
+                min_size: DEFAULT_MIN_SIZE,
+                content_types: DEFAULT_CONTENT_TYPES
+                    .iter()
+                    .map(|content_type| content_type.to_string())
+                    .collect(),
+            }),
+        }
+    }
+
+    /// Enabled encodings in the order preferred when the client accepts several equally
+    pub fn encodings(mut self, encodings: Vec<Encoding>) -> Self {
+        self.encodings = encodings;
+        self
+    }
+
+    /// Minimum size in bytes of the responses with a known length
+    pub fn min_size(mut self, min_size: u64) -> Self {
+        Arc::make_mut(&mut self.filter).min_size = min_size;
+        self
+    }
+
+    /// Replace the content type allowlist, entries ending with `/` match the whole type
+    pub fn content_types<'a>(mut self, content_types: impl IntoIterator<Item = &'a str>) -> Self {
+        Arc::make_mut(&mut self.filter).content_types = content_types
+            .into_iter()
+            .map(|content_type| content_type.to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// Enabled encoding with the highest quality in the accept-encoding
+    fn negotiate(&self, accept_encoding: &str) -> Option<Encoding> {
+        let mut best: Option<(Encoding, f32)> = None;
+
+        for encoding in self.encodings.iter() {
+            let quality = quality(accept_encoding, encoding.token());
+
+            if quality > 0.0 && best.is_none_or(|(_, best)| quality > best) {
+                best = Some((*encoding, quality));
+            }
+        }
+
+        best.map(|(encoding, _)| encoding)
+    }
+}
+
+impl ResponseFilter {
+    fn is_allowed_type(&self, content_type: &str) -> bool {
+        let media_type = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        self.content_types.iter().any(|allowed| {
+            if allowed.ends_with('/') {
+                media_type.starts_with(allowed.as_str())
+            } else {
+                media_type == *allowed
+            }
+        })
+    }
+
+    /// Whether the response could be compressed for some accept-encoding
+    fn is_compressible(&self, res: &Response<Body>) -> bool {
+        let headers = res.headers();
+        let status = res.status();
+
+        if status.is_informational()
+            || status == StatusCode::NO_CONTENT
+            || status == StatusCode::NOT_MODIFIED
+            || status == StatusCode::PARTIAL_CONTENT
+            || headers.contains_key(CONTENT_ENCODING)
+            || headers.contains_key(CONTENT_RANGE)
+        {
+            return false;
+        }
+
+        let no_transform = headers.get_all(CACHE_CONTROL).iter().any(|value| {
+            value
+                .to_str()
+                .is_ok_and(|value| value.contains("no-transform"))
+        });
+
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok());
+
+        let size = headers
+            .get(CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok())
+            .and_then(|len| len.parse().ok())
+            .or_else(|| res.body().content_length());
+
+        !no_transform
+            && content_type.is_some_and(|content_type| self.is_allowed_type(content_type))
+            && size.is_none_or(|size| size >= self.min_size)
+    }
+}
+
+/// Quality of the coding in the accept-encoding, `*` applies to the unlisted codings
+fn quality(accept_encoding: &str, coding: &str) -> f32 {
+    let mut wildcard = None;
+
+    for item in accept_encoding.split(',') {
+        let mut params = item.split(';');
+        let name = params.next().unwrap_or_default().trim();
+
+        let quality = params
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        if name.eq_ignore_ascii_case(coding) {
+            return quality;
+        }
+
+        if name == "*" {
+            wildcard = Some(quality);
+        }
+    }
+
+    wildcard.unwrap_or(0.0)
+}
+
+/// Compressed bytes differ from the identity representation, so a strong validator becomes weak
+fn weaken_etag(headers: &mut HeaderMap) {
+    let weak = headers
+        .get(ETAG)
+        .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+        .and_then(|etag| HeaderValue::from_bytes(&[b"W/", etag.as_bytes()].concat()).ok());
+
+    if let Some(weak) = weak {
+        headers.insert(ETAG, weak);
+    }
+}
+
+impl Middleware for Compression {
+    fn handle<'a>(
+        &'a self,
+        context: Context,
+        ep_executor: EndpointExecutor<'a>,
+    ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        let encoding = context
+            .headers()
+            .get(ACCEPT_ENCODING)
+            .and_then(|accept_encoding| accept_encoding.to_str().ok())
+            .and_then(|accept_encoding| self.negotiate(accept_encoding))
+            // Response to HEAD has no body to compress
+            .filter(|_| context.method() != Method::HEAD);
+        let filter = self.filter.clone();
+
+        Box::new(ep_executor.next(context).map(move |res| {
+            if !filter.is_compressible(&res) {
+                return res;
+            }
+
+            let (mut parts, body) = res.into_parts();
+            add_vary(&mut parts.headers, "accept-encoding");
+
+            let encoding = match encoding {
+                Some(encoding) => encoding,
+                None => return Response::from_parts(parts, body),
+            };
+
+            parts.headers.remove(CONTENT_LENGTH);
+            parts
+                .headers
+                .insert(CONTENT_ENCODING, HeaderValue::from_static(encoding.token()));
+            weaken_etag(&mut parts.headers);
+
+            let body = Body::wrap_stream(CompressStream::new(body, encoding));
+
+            Response::from_parts(parts, body)
+        }))
+    }
+}
+
+enum Encoder {
+    Brotli(Box<CompressorWriter<Vec<u8>>>),
+    Gzip(GzEncoder<Vec<u8>>),
+    Deflate(ZlibEncoder<Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Brotli => {
+                Encoder::Brotli(Box::new(CompressorWriter::new(vec![], 4096, 5, 22)))
+            }
+            Encoding::Gzip => Encoder::Gzip(GzEncoder::new(vec![], flate2::Compression::default())),
+            Encoding::Deflate => {
+                Encoder::Deflate(ZlibEncoder::new(vec![], flate2::Compression::default()))
+            }
+        }
+    }
+
+    /// Compress the chunk and take the output flushed so far
+    fn compress(&mut self, chunk: &[u8]) -> io::Result<Vec<u8>> {
+        let output = match self {
+            Encoder::Brotli(encoder) => {
+                encoder.write_all(chunk)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+            Encoder::Gzip(encoder) => {
+                encoder.write_all(chunk)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+            Encoder::Deflate(encoder) => {
+                encoder.write_all(chunk)?;
+                encoder.flush()?;
+                encoder.get_mut()
+            }
+        };
+
+        Ok(mem::take(output))
+    }
+
+    fn finish(self) -> io::Result<Vec<u8>> {
+        match self {
+            Encoder::Brotli(encoder) => Ok(encoder.into_inner()),
+            Encoder::Gzip(encoder) => encoder.finish(),
+            Encoder::Deflate(encoder) => encoder.finish(),
+        }
+    }
+}
+
+/// CompressStream compresses each chunk of the body as it arrives
+///
+/// Every chunk is flushed so that streamed responses are not held back by the encoder.
+struct CompressStream {
+    body: Body,
+    encoder: Option<Encoder>,
+}
+
+impl CompressStream {
+    fn new(body: Body, encoding: Encoding) -> Self {
+        CompressStream {
+            body,
+            encoder: Some(Encoder::new(encoding)),
+        }
+    }
+}
+
+impl Stream for CompressStream {
+    type Item = Vec<u8>;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            let encoder = match self.encoder.as_mut() {
+                Some(encoder) => encoder,
+                None => return Ok(Async::Ready(None)),
+            };
+
+            match self.body.poll()? {
+                Async::NotReady => return Ok(Async::NotReady),
+                Async::Ready(Some(chunk)) => {
+                    let output = encoder.compress(&chunk)?;
+
+                    if !output.is_empty() {
+                        return Ok(Async::Ready(Some(output)));
+                    }
+                }
+                Async::Ready(None) => {
+                    let output = self.encoder.take().map(Encoder::finish).transpose()?;
+
+                    return Ok(Async::Ready(output.filter(|output| !output.is_empty())));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compression_negotiate_test() {
+        let compression = Compression::new();
+
+        let test_cases = [
+            ("gzip, deflate, br", Some(Encoding::Brotli)),
+            ("gzip;q=1.0, br;q=0.5", Some(Encoding::Gzip)),
+            ("deflate", Some(Encoding::Deflate)),
+            ("br;q=0, *;q=0.1", Some(Encoding::Gzip)),
+            ("identity", None),
+            ("*;q=0", None),
+            ("", None),
+        ];
+
+        for case in test_cases.iter() {
+            assert_eq!(compression.negotiate(case.0), case.1, "{}", case.0);
+        }
+
+        let gzip_only = Compression::new().encodings(vec![Encoding::Gzip]);
+
+        assert_eq!(gzip_only.negotiate("br"), None);
+    }
+
+    #[test]
+    fn compression_content_type_test() {
+        let filter = Compression::new().filter;
+
+        assert!(filter.is_allowed_type("text/html; charset=utf-8"));
+        assert!(filter.is_allowed_type("Application/JSON"));
+        assert!(!filter.is_allowed_type("image/png"));
+    }
+
+    #[test]
+    fn compression_weaken_etag_test() {
+        let test_cases = [("\"abc\"", "W/\"abc\""), ("W/\"abc\"", "W/\"abc\"")];
+
+        for case in test_cases.iter() {
+            let mut headers = HeaderMap::new();
+            headers.insert(ETAG, HeaderValue::from_static(case.0));
+
+            weaken_etag(&mut headers);
+
+            assert_eq!(headers[ETAG], case.1);
+        }
+    }
+}
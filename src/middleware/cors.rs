@@ -0,0 +1,416 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use futures::{future, Future};
+use regex::Regex;
+
+use crate::app::EndpointExecutor;
+use crate::context::Context;
+use crate::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCESS_CONTROL_ALLOW_CREDENTIALS,
+    ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS,
+    ACCESS_CONTROL_REQUEST_METHOD, ORIGIN,
+};
+use crate::middleware::{add_vary, Middleware};
+use crate::{Body, Method, Response, StatusCode};
+
+enum OriginRule {
+    Any,
+    Exact(String),
+    Regex(Regex),
+    Predicate(Box<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+impl OriginRule {
+    fn allows(&self, origin: &str) -> bool {
+        match self {
+            OriginRule::Any => true,
+            OriginRule::Exact(allowed) => allowed == origin,
+            OriginRule::Regex(regex) => regex.is_match(origin),
+            OriginRule::Predicate(predicate) => predicate(origin),
+        }
+    }
+}
+
+/// Cors adds the CORS headers to the cross-origin responses and answers the preflight requests
+///
+/// Apply it as pre-routing middleware so that the preflight requests are answered
+/// even if the path has no OPTIONS route.
+///
+/// # Example
+///
+/// ```
+/// # use std::time::Duration;
+/// # use obsidian::{App, Method};
+/// # use obsidian::middleware::Cors;
+/// let mut app = App::new();
+///
+/// app.use_pre_routing(
+///     Cors::new()
+///         .allow_origin("https://app.example.com")
+///         .allow_origin_regex(r"^https://[a-z]+\.preview\.example\.com$")
+///         .allow_methods(vec![Method::GET, Method::POST])
+///         .allow_headers(vec!["content-type", "authorization"])
+///         .allow_credentials(true)
+///         .max_age(Duration::from_secs(3600)),
+/// );
+/// ```
+pub struct Cors {
+    origins: Vec<OriginRule>,
+    methods: Vec<Method>,
+    headers: Option<HashSet<HeaderName>>,
+    expose_headers: Vec<HeaderName>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Cors {
+    /// No origin is allowed until configured, the common methods and no extra headers are allowed
+    pub fn new() -> Self {
+        Cors {
+            origins: vec![],
+            methods: vec![
+                Method::GET,
+                Method::HEAD,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ],
+            headers: Some(HashSet::new()),
+            expose_headers: vec![],
+            credentials: false,
+            max_age: None,
+        }
+    }
+
+    /// Panic if the credentials are allowed, any site could read the credentialed responses
+    pub fn allow_any_origin(mut self) -> Self {
+        if self.credentials {
+            panic!("ERROR: CORS credentials cannot be allowed for any origin");
+        }
+
+        self.origins.push(OriginRule::Any);
+        self
+    }
+
+    pub fn allow_origin(mut self, origin: &str) -> Self {
+        self.origins.push(OriginRule::Exact(origin.to_string()));
+        self
+    }
+
+    pub fn allow_origins<'a>(mut self, origins: impl IntoIterator<Item = &'a str>) -> Self {
+        for origin in origins {
+            self = self.allow_origin(origin);
+        }
+        self
+    }
+
+    /// Panic if the regex is invalid
+    pub fn allow_origin_regex(mut self, regex: &str) -> Self {
+        let regex = Regex::new(regex)
+            .unwrap_or_else(|err| panic!("ERROR: Invalid CORS origin regex: {}", err));
+
+        self.origins.push(OriginRule::Regex(regex));
+        self
+    }
+
+    pub fn allow_origin_fn(
+        mut self,
+        predicate: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.origins
+            .push(OriginRule::Predicate(Box::new(predicate)));
+        self
+    }
+
+    /// Replace the allowed methods
+    pub fn allow_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Add the allowed request headers, panic if a header name is invalid
+    pub fn allow_headers<'a>(mut self, headers: impl IntoIterator<Item = &'a str>) -> Self {
+        let allowed = self.headers.get_or_insert_with(HashSet::new);
+
+        allowed.extend(headers.into_iter().map(parse_header_name));
+        self
+    }
+
+    /// Allow whatever headers the preflight request asks for
+    pub fn allow_any_header(mut self) -> Self {
+        self.headers = None;
+        self
+    }
+
+    /// Response headers readable by the browser, panic if a header name is invalid
+    pub fn expose_headers<'a>(mut self, headers: impl IntoIterator<Item = &'a str>) -> Self {
+        self.expose_headers
+            .extend(headers.into_iter().map(parse_header_name));
+        self
+    }
+
+    /// Panic if any origin is allowed, any site could read the credentialed responses
+    pub fn allow_credentials(mut self, credentials: bool) -> Self {
+        if credentials && self.is_wildcard() {
+            panic!("ERROR: CORS credentials cannot be allowed for any origin");
+        }
+
+        self.credentials = credentials;
+        self
+    }
+
+    /// Duration the browser may cache the preflight response
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    fn allows_origin(&self, origin: &str) -> bool {
+        self.origins.iter().any(|rule| rule.allows(origin))
+    }
+
+    fn is_wildcard(&self) -> bool {
+        self.origins
+            .iter()
+            .any(|rule| matches!(rule, OriginRule::Any))
+    }
+
+    fn allows_headers(&self, requested: &str) -> bool {
+        let allowed = match &self.headers {
+            Some(allowed) => allowed,
+            None => return true,
+        };
+
+        requested
+            .split(',')
+            .map(|header| header.trim())
+            .filter(|header| !header.is_empty())
+            .all(|header| {
+                HeaderName::from_bytes(header.as_bytes())
+                    .map(|header| allowed.contains(&header))
+                    .unwrap_or(false)
+            })
+    }
+
+    /// Allow origin and credentials headers shared by the preflight and actual responses
+    fn add_origin_headers(&self, headers: &mut HeaderMap, origin: &HeaderValue) {
+        if self.is_wildcard() {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+        } else {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+        }
+
+        if self.credentials {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+
+    fn preflight(&self, context: &Context, origin: &HeaderValue) -> Response<Body> {
+        let mut res = Response::new(Body::empty());
+        *res.status_mut() = StatusCode::NO_CONTENT;
+
+        let method = context
+            .headers()
+            .get(ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|method| Method::from_bytes(method.as_bytes()).ok());
+        let requested_headers = context
+            .headers()
+            .get(ACCESS_CONTROL_REQUEST_HEADERS)
+            .and_then(|headers| headers.to_str().ok())
+            .unwrap_or_default();
+
+        let allowed = origin
+            .to_str()
+            .is_ok_and(|origin| self.allows_origin(origin))
+            && method.is_some_and(|method| self.methods.contains(&method))
+            && self.allows_headers(requested_headers);
+
+        add_vary(
+            res.headers_mut(),
+            "origin, access-control-request-method, access-control-request-headers",
+        );
+
+        if !allowed {
+            *res.status_mut() = StatusCode::FORBIDDEN;
+            *res.body_mut() = Body::from("403 Forbidden");
+            return res;
+        }
+
+        let headers = res.headers_mut();
+
+        self.add_origin_headers(headers, origin);
+
+        if let Ok(methods) = HeaderValue::from_str(&join(self.methods.iter())) {
+            headers.insert(ACCESS_CONTROL_ALLOW_METHODS, methods);
+        }
+
+        let allow_headers = match &self.headers {
+            Some(allowed) => {
+                let mut allowed = allowed
+                    .iter()
+                    .map(HeaderName::as_str)
+                    .collect::<Vec<&str>>();
+                allowed.sort_unstable();
+
+                HeaderValue::from_str(&allowed.join(", ")).ok()
+            }
+            None => HeaderValue::from_str(requested_headers).ok(),
+        };
+
+        if let Some(allow_headers) = allow_headers.filter(|value| !value.is_empty()) {
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, allow_headers);
+        }
+
+        if let Some(max_age) = self.max_age {
+            headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age.as_secs()));
+        }
+
+        res
+    }
+}
+
+fn parse_header_name(header: &str) -> HeaderName {
+    HeaderName::from_bytes(header.as_bytes())
+        .unwrap_or_else(|_| panic!("ERROR: Invalid CORS header {}", header))
+}
+
+fn join<T: AsRef<str>>(values: impl Iterator<Item = T>) -> String {
+    values
+        .map(|value| value.as_ref().to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl Middleware for Cors {
+    fn handle<'a>(
+        &'a self,
+        context: Context,
+        ep_executor: EndpointExecutor<'a>,
+    ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        let origin = context.headers().get(ORIGIN).cloned();
+        let vary_origin = !self.is_wildcard();
+
+        let origin = match origin {
+            Some(origin) => origin,
+            None => {
+                return Box::new(ep_executor.next(context).map(move |mut res| {
+                    if vary_origin {
+                        add_vary(res.headers_mut(), "origin");
+                    }
+                    res
+                }))
+            }
+        };
+
+        let is_preflight = context.method() == Method::OPTIONS
+            && context
+                .headers()
+                .contains_key(ACCESS_CONTROL_REQUEST_METHOD);
+
+        if is_preflight {
+            return Box::new(future::ok(self.preflight(&context, &origin)));
+        }
+
+        let allowed = origin
+            .to_str()
+            .is_ok_and(|origin| self.allows_origin(origin));
+        let mut cors_headers = HeaderMap::new();
+
+        if allowed {
+            self.add_origin_headers(&mut cors_headers, &origin);
+
+            if !self.expose_headers.is_empty() {
+                if let Ok(expose) = HeaderValue::from_str(&join(self.expose_headers.iter())) {
+                    cors_headers.insert(ACCESS_CONTROL_EXPOSE_HEADERS, expose);
+                }
+            }
+        }
+
+        Box::new(ep_executor.next(context).map(move |mut res| {
+            res.headers_mut().extend(cors_headers);
+
+            if vary_origin {
+                add_vary(res.headers_mut(), "origin");
+            }
+
+            res
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cors_origin_test() {
+        let cors = Cors::new()
+            .allow_origins(vec!["https://a.example.com", "https://b.example.com"])
+            .allow_origin_regex(r"^https://[a-z]+\.preview\.example\.com$")
+            .allow_origin_fn(|origin| origin.ends_with(".internal"));
+
+        let test_cases = [
+            ("https://a.example.com", true),
+            ("https://b.example.com", true),
+            ("https://pr.preview.example.com", true),
+            ("http://api.internal", true),
+            ("https://c.example.com", false),
+            ("https://a.example.com.evil.com", false),
+        ];
+
+        for case in test_cases.iter() {
+            assert_eq!(cors.allows_origin(case.0), case.1, "{}", case.0);
+        }
+    }
+
+    #[test]
+    fn cors_headers_test() {
+        let cors = Cors::new().allow_headers(vec!["Content-Type", "x-token"]);
+
+        assert!(cors.allows_headers("content-type, X-Token"));
+        assert!(cors.allows_headers(""));
+        assert!(!cors.allows_headers("content-type, authorization"));
+        assert!(Cors::new()
+            .allow_any_header()
+            .allows_headers("authorization"));
+    }
+
+    #[test]
+    fn cors_wildcard_test() {
+        assert!(Cors::new().allow_any_origin().is_wildcard());
+        assert!(!Cors::new()
+            .allow_origin("https://a.example.com")
+            .allow_credentials(true)
+            .is_wildcard());
+    }
+
+    #[should_panic]
+    #[test]
+    fn cors_any_origin_with_credentials_test() {
+        Cors::new().allow_any_origin().allow_credentials(true);
+    }
+
+    #[should_panic]
+    #[test]
+    fn cors_credentials_with_any_origin_test() {
+        Cors::new().allow_credentials(true).allow_any_origin();
+    }
+
+    #[should_panic]
+    #[test]
+    fn cors_invalid_regex_test() {
+        Cors::new().allow_origin_regex("(");
+    }
+}
@@ -0,0 +1,59 @@
+use std::marker::PhantomData;
+
+use futures::Future;
+
+use crate::app::EndpointExecutor;
+use crate::context::Context;
+use crate::middleware::Middleware;
+use crate::{Body, Response};
+
+/// FnMiddleware adapts a closure receiving the context and the next executor
+///
+/// # Example
+///
+/// ```
+/// # use futures::Future;
+/// # use obsidian::header::HeaderValue;
+/// # use obsidian::middleware::FnMiddleware;
+/// # use obsidian::router::Router;
+/// let mut router = Router::new();
+///
+/// router.use_service(FnMiddleware::new(|ctx, next| {
+///     next.next(ctx).map(|mut res| {
+///         res.headers_mut()
+///             .insert("x-powered-by", HeaderValue::from_static("obsidian"));
+///         res
+///     })
+/// }));
+/// ```
+pub struct FnMiddleware<F, R> {
+    f: F,
+    _response: PhantomData<fn() -> R>,
+}
+
+impl<F, R> FnMiddleware<F, R>
+where
+    F: Fn(Context, EndpointExecutor<'_>) -> R + Send + Sync + 'static,
+    R: Future<Item = Response<Body>, Error = hyper::Error> + Send + 'static,
+{
+    pub fn new(f: F) -> Self {
+        FnMiddleware {
+            f,
+            _response: PhantomData,
+        }
+    }
+}
+
+impl<F, R> Middleware for FnMiddleware<F, R>
+where
+    F: Fn(Context, EndpointExecutor<'_>) -> R + Send + Sync + 'static,
+    R: Future<Item = Response<Body>, Error = hyper::Error> + Send + 'static,
+{
+    fn handle<'a>(
+        &'a self,
+        context: Context,
+        ep_executor: EndpointExecutor<'a>,
+    ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        Box::new((self.f)(context, ep_executor))
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::Future;
+
+use crate::app::EndpointExecutor;
+use crate::context::Context;
+use crate::middleware::Middleware;
+use crate::{Body, HeaderMap, Method, Response, Uri, Version};
+
+/// RequestInfo is the request metadata passed to `Hook::after` once the response is ready
+#[derive(Clone, Debug)]
+pub struct RequestInfo {
+    pub method: Method,
+    pub uri: Uri,
+    pub version: Version,
+    pub headers: HeaderMap,
+    /// Time spent by the rest of the middleware chain and the handler
+    pub elapsed: Duration,
+}
+
+/// Hook runs around the rest of the middleware chain without chaining futures
+///
+/// # Example
+///
+/// ```
+/// # use obsidian::{Body, Response};
+/// # use obsidian::header::HeaderValue;
+/// # use obsidian::middleware::{Hook, RequestInfo};
+/// # use obsidian::router::Router;
+/// struct ResponseTime;
+///
+/// impl Hook for ResponseTime {
+///     fn after(&self, info: &RequestInfo, res: &mut Response<Body>) {
+///         let elapsed = format!("{}ms", info.elapsed.as_millis());
+///
+///         res.headers_mut()
+///             .insert("x-response-time", HeaderValue::from_str(&elapsed).unwrap());
+///     }
+/// }
+///
+/// let mut router = Router::new();
+///
+/// router.use_hook(ResponseTime);
+/// ```
+pub trait Hook: Send + Sync + 'static {
+    /// Inspect or modify the request before the rest of the chain
+    fn before(&self, _ctx: &mut Context) {}
+
+    /// Inspect or modify the response of the rest of the chain
+    fn after(&self, _info: &RequestInfo, _res: &mut Response<Body>) {}
+
+    /// Name used by the route introspection, type name by default
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// HookMiddleware adapts a hook to the middleware chain
+pub struct HookMiddleware<H> {
+    hook: Arc<H>,
+}
+
+impl<H: Hook> HookMiddleware<H> {
+    pub fn new(hook: H) -> Self {
+        HookMiddleware {
+            hook: Arc::new(hook),
+        }
+    }
+}
+
+impl<H: Hook> Middleware for HookMiddleware<H> {
+    fn handle<'a>(
+        &'a self,
+        mut context: Context,
+        ep_executor: EndpointExecutor<'a>,
+    ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        self.hook.before(&mut context);
+
+        let start = Instant::now();
+        let mut info = RequestInfo {
+            method: context.method().clone(),
+            uri: context.uri().clone(),
+            version: context.version(),
+            headers: context.headers().clone(),
+            elapsed: Duration::default(),
+        };
+        let hook = self.hook.clone();
+
+        Box::new(ep_executor.next(context).map(move |mut res| {
+            info.elapsed = start.elapsed();
+            hook.after(&info, &mut res);
+            res
+        }))
+    }
+
+    fn name(&self) -> &'static str {
+        self.hook.name()
+    }
+}
@@ -0,0 +1,181 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::ObsidianError;
+
+/// LogSink is the destination of the access log lines
+///
+/// Write errors are ignored so that logging never fails the request.
+pub enum LogSink {
+    Stdout,
+    Stderr,
+    File(RotatingFile),
+}
+
+impl LogSink {
+    /// Append to the file and rotate it once it would exceed `max_size` bytes
+    /// At most `max_files` rotated files are kept as `<path>.1` to `<path>.<max_files>`
+    pub fn file(
+        path: impl AsRef<Path>,
+        max_size: u64,
+        max_files: usize,
+    ) -> Result<Self, ObsidianError> {
+        RotatingFile::open(path.as_ref(), max_size, max_files)
+            .map(LogSink::File)
+            .map_err(|err| ObsidianError::GeneralError(err.to_string()))
+    }
+
+    pub(crate) fn write_line(&self, line: &str) {
+        let _ = match self {
+            LogSink::Stdout => writeln!(io::stdout().lock(), "{}", line),
+            LogSink::Stderr => writeln!(io::stderr().lock(), "{}", line),
+            LogSink::File(file) => file.write_line(line),
+        };
+    }
+}
+
+/// RotatingFile is the file sink rotated by size
+///
+/// Lines are written and rotated by a background thread so that the file I/O
+/// never blocks the response futures. Dropping the sink waits for the pending lines.
+pub struct RotatingFile {
+    sender: Option<Sender<String>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+struct FileWriter {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, max_size: u64, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+
+        let mut writer = FileWriter {
+            path: path.to_path_buf(),
+            max_size,
+            max_files,
+            file,
+            size,
+        };
+        let (sender, receiver) = mpsc::channel::<String>();
+
+        let writer = thread::Builder::new()
+            .name("obsidian-log".to_string())
+            .spawn(move || {
+                for line in receiver {
+                    let _ = writer.write_line(&line);
+                }
+            })?;
+
+        Ok(RotatingFile {
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        if let Some(sender) = &self.sender {
+            sender
+                .send(line.to_string())
+                .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Log writer stopped"))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for RotatingFile {
+    fn drop(&mut self) {
+        // Closing the channel stops the writer after the pending lines
+        self.sender.take();
+
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+impl FileWriter {
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+
+        if self.size > 0 && self.size + len > self.max_size {
+            self.file = self.rotate()?;
+            self.size = 0;
+        }
+
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+
+        Ok(())
+    }
+
+    /// Shift the rotated files by one and start a new file
+    fn rotate(&self) -> io::Result<File> {
+        if self.max_files > 0 {
+            let _ = fs::remove_file(self.rotated_path(self.max_files));
+
+            for index in (1..self.max_files).rev() {
+                let _ = fs::rename(self.rotated_path(index), self.rotated_path(index + 1));
+            }
+
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotating_file_test() {
+        let dir = std::env::temp_dir().join(format!("obsidian-log-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("access.log");
+
+        let sink = LogSink::file(&path, 10, 2).unwrap();
+
+        for line in ["first", "second", "third", "fourth"].iter() {
+            sink.write_line(line);
+        }
+
+        // Wait for the background writer
+        drop(sink);
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("access.log.1")).unwrap(),
+            "third\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("access.log.2")).unwrap(),
+            "second\n"
+        );
+        assert!(!dir.join("access.log.3").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
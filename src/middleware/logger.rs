@@ -1,16 +1,82 @@
+use std::fmt::Write;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
 use futures::Future;
+use hyper::body::Payload;
+use serde_json::json;
 
 use crate::app::EndpointExecutor;
-use crate::context::Context;
-use crate::middleware::Middleware;
+use crate::context::{Context, RequestIdValue};
+use crate::header::{self, HeaderMap, HeaderName};
+use crate::middleware::{LogSink, Middleware, TraceContext};
 use crate::{Body, Response};
 
-#[derive(Default)]
-pub struct Logger {}
+/// Header carrying the request ID by default
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// LogFormat is the layout of an access log line
+#[derive(Clone, Debug)]
+pub enum LogFormat {
+    /// Common Log Format
+    Common,
+    /// Combined Log Format, the common format with the referer and user agent
+    Combined,
+    /// One JSON object per line
+    Json,
+    /// Template with the `{field}` placeholders replaced, unknown placeholders are kept
+    ///
+    /// Fields are `remote_addr`, `time`, `method`, `uri`, `path`, `version`, `status`,
+    /// `size`, `latency_ms`, `referer`, `user_agent`, `request_id`, `trace_id`, `span_id` and `host`.
+    Template(String),
+}
+
+/// Logger records an access log line once the response is ready
+///
+/// Missing values are logged as `-`, so the logger never fails the request.
+///
+/// # Example
+///
+/// ```
+/// # use obsidian::App;
+/// # use obsidian::middleware::{LogFormat, LogSink, Logger};
+/// let mut app = App::new();
+///
+/// app.use_pre_routing(
+///     Logger::new()
+///         .format(LogFormat::Template("{method} {path} {status} {latency_ms}ms".to_string()))
+///         .sink(LogSink::Stderr),
+/// );
+/// ```
+pub struct Logger {
+    format: LogFormat,
+    sink: Arc<LogSink>,
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Logger {
+    /// Common Log Format written to stdout
     pub fn new() -> Self {
-        Logger {}
+        Logger {
+            format: LogFormat::Common,
+            sink: Arc::new(LogSink::Stdout),
+        }
+    }
+
+    pub fn format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn sink(mut self, sink: LogSink) -> Self {
+        self.sink = Arc::new(sink);
+        self
     }
 }
 
@@ -20,13 +86,326 @@ impl Middleware for Logger {
         context: Context,
         ep_executor: EndpointExecutor<'a>,
     ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
-        println!(
-            "{} {} \n{}",
-            context.method(),
-            context.uri(),
-            context.headers().get("host").unwrap().to_str().unwrap()
+        let start = Instant::now();
+        let mut record = LogRecord::new(&context);
+        let format = self.format.clone();
+        let sink = self.sink.clone();
+
+        Box::new(ep_executor.next(context).map(move |res| {
+            record.complete(&res, start.elapsed());
+            sink.write_line(&record.format(&format));
+            res
+        }))
+    }
+}
+
+/// LogRecord is the data of a single access log line
+#[derive(Clone, Debug)]
+struct LogRecord {
+    remote_addr: Option<IpAddr>,
+    time: SystemTime,
+    method: String,
+    uri: String,
+    path: String,
+    version: String,
+    status: u16,
+    size: Option<u64>,
+    latency: Duration,
+    referer: Option<String>,
+    user_agent: Option<String>,
+    request_id: Option<String>,
+    trace_id: Option<String>,
+    span_id: Option<String>,
+    host: Option<String>,
+}
+
+impl LogRecord {
+    fn new(context: &Context) -> Self {
+        let headers = context.headers();
+
+        let mut record = LogRecord {
+            remote_addr: context.remote_addr().map(|addr| addr.ip()),
+            time: SystemTime::now(),
+            method: context.method().to_string(),
+            uri: context.uri().to_string(),
+            path: context.uri().path().to_string(),
+            version: format!("{:?}", context.version()),
+            status: 0,
+            size: None,
+            latency: Duration::default(),
+            referer: header_value(headers, &header::REFERER),
+            user_agent: header_value(headers, &header::USER_AGENT),
+            request_id: context
+                .request_id()
+                .map(String::from)
+                .or_else(|| header_value(headers, &HeaderName::from_static(REQUEST_ID_HEADER))),
+            trace_id: None,
+            span_id: None,
+            host: header_value(headers, &header::HOST),
+        };
+
+        record.set_trace_context(context.trace_context());
+        record
+    }
+
+    fn complete(&mut self, res: &Response<Body>, latency: Duration) {
+        self.status = res.status().as_u16();
+        self.size = header_value(res.headers(), &header::CONTENT_LENGTH)
+            .and_then(|len| len.parse().ok())
+            .or_else(|| res.body().content_length());
+        self.latency = latency;
+
+        // Request ID assigned after the logger is only known by the response
+        if self.request_id.is_none() {
+            self.request_id = res
+                .extensions()
+                .get::<RequestIdValue>()
+                .map(|request_id| request_id.0.to_string())
+                .or_else(|| {
+                    header_value(res.headers(), &HeaderName::from_static(REQUEST_ID_HEADER))
+                });
+        }
+
+        if self.trace_id.is_none() {
+            self.set_trace_context(res.extensions().get::<TraceContext>());
+        }
+    }
+
+    fn set_trace_context(&mut self, trace_context: Option<&TraceContext>) {
+        if let Some(trace_context) = trace_context {
+            self.trace_id = Some(trace_context.trace_id());
+            self.span_id = Some(trace_context.span_id());
+        }
+    }
+
+    fn format(&self, format: &LogFormat) -> String {
+        match format {
+            LogFormat::Common => self.common(),
+            LogFormat::Combined => format!(
+                "{} \"{}\" \"{}\"",
+                self.common(),
+                quoted(&self.referer),
+                quoted(&self.user_agent)
+            ),
+            LogFormat::Json => json!({
+                "remote_addr": self.remote_addr.map(|addr| addr.to_string()),
+                "time": rfc3339(self.time),
+                "method": self.method,
+                "uri": self.uri,
+                "version": self.version,
+                "status": self.status,
+                "size": self.size,
+                "latency_ms": self.latency_ms(),
+                "referer": self.referer,
+                "user_agent": self.user_agent,
+                "request_id": self.request_id,
+                "trace_id": self.trace_id,
+                "span_id": self.span_id,
+                "host": self.host,
+            })
+            .to_string(),
+            LogFormat::Template(template) => self.template(template),
+        }
+    }
+
+    fn common(&self) -> String {
+        format!(
+            "{} - - [{}] \"{} {} {}\" {} {}",
+            self.field("remote_addr").unwrap_or_default(),
+            clf_time(self.time),
+            self.method,
+            self.uri,
+            self.version,
+            self.status,
+            self.field("size").unwrap_or_default()
+        )
+    }
+
+    fn template(&self, template: &str) -> String {
+        let mut line = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            line.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let value = rest
+                .find('}')
+                .and_then(|end| self.field(&rest[1..end]).map(|value| (value, end)));
+
+            match value {
+                Some((value, end)) => {
+                    line.push_str(&value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    line.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+
+        line.push_str(rest);
+        line
+    }
+
+    /// Value of the template field, `-` if missing and None if the field is unknown
+    fn field(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "remote_addr" => self.remote_addr.map(|addr| addr.to_string()),
+            "time" => Some(clf_time(self.time)),
+            "method" => Some(self.method.clone()),
+            "uri" => Some(self.uri.clone()),
+            "path" => Some(self.path.clone()),
+            "version" => Some(self.version.clone()),
+            "status" => Some(self.status.to_string()),
+            "size" => self.size.map(|size| size.to_string()),
+            "latency_ms" => Some(format!("{:.3}", self.latency_ms())),
+            "referer" => self.referer.clone(),
+            "user_agent" => self.user_agent.clone(),
+            "request_id" => self.request_id.clone(),
+            "trace_id" => self.trace_id.clone(),
+            "span_id" => self.span_id.clone(),
+            "host" => self.host.clone(),
+            _ => return None,
+        };
+
+        Some(value.unwrap_or_else(|| "-".to_string()))
+    }
+
+    fn latency_ms(&self) -> f64 {
+        self.latency.as_secs_f64() * 1000.0
+    }
+}
+
+fn header_value(headers: &HeaderMap, name: &HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+}
+
+/// Quoted field of the combined format with the quotes escaped
+fn quoted(value: &Option<String>) -> String {
+    match value {
+        Some(value) => value.replace('\\', "\\\\").replace('"', "\\\""),
+        None => "-".to_string(),
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Time in the Common Log Format, for example `10/Oct/2000:13:55:36 +0000`
+fn clf_time(time: SystemTime) -> String {
+    let (year, month, day, secs) = utc_date(time);
+    let mut formatted = String::with_capacity(26);
+
+    let _ = write!(
+        formatted,
+        "{:02}/{}/{}:{:02}:{:02}:{:02} +0000",
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    );
+
+    formatted
+}
+
+/// Time in RFC 3339, for example `2000-10-10T13:55:36Z`
+fn rfc3339(time: SystemTime) -> String {
+    let (year, month, day, secs) = utc_date(time);
+
+    format!(
+        "{}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Civil UTC date and the seconds of the day
+fn utc_date(time: SystemTime) -> (i64, u32, u32, u64) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+
+    // Days to civil date from http://howardhinnant.github.io/date_algorithms.html
+    let days = (secs / 86400) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day, secs % 86400)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Request;
+    use std::collections::HashMap;
+
+    fn record() -> LogRecord {
+        let req = Request::builder()
+            .uri("/users?page=2")
+            .header(header::USER_AGENT, "curl/7.64.1")
+            .header(REQUEST_ID_HEADER, "abc")
+            .body(Body::empty())
+            .unwrap();
+        let mut record = LogRecord::new(&Context::new(req, HashMap::default()));
+
+        let res = Response::new(Body::from("users"));
+        record.complete(&res, Duration::from_millis(12));
+        record.time = UNIX_EPOCH + Duration::from_secs(971_186_136);
+
+        record
+    }
+
+    #[test]
+    fn logger_common_format_test() {
+        assert_eq!(
+            record().format(&LogFormat::Common),
+            "- - - [10/Oct/2000:13:55:36 +0000] \"GET /users?page=2 HTTP/1.1\" 200 5"
         );
+        assert_eq!(
+            record().format(&LogFormat::Combined),
+            "- - - [10/Oct/2000:13:55:36 +0000] \"GET /users?page=2 HTTP/1.1\" 200 5 \"-\" \"curl/7.64.1\""
+        );
+    }
+
+    #[test]
+    fn logger_json_format_test() {
+        let line = record().format(&LogFormat::Json);
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(json["time"], "2000-10-10T13:55:36Z");
+        assert_eq!(json["status"], 200);
+        assert_eq!(json["request_id"], "abc");
+        assert!(json["remote_addr"].is_null());
+    }
+
+    #[test]
+    fn logger_template_format_test() {
+        let template =
+            LogFormat::Template("{method} {path} {status} {host} {unknown} {".to_string());
 
-        ep_executor.next(context)
+        assert_eq!(record().format(&template), "GET /users 200 - {unknown} {");
     }
 }
@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use futures::Future;
+
+use crate::app::EndpointExecutor;
+use crate::context::{Context, RoutePattern};
+use crate::header::CONTENT_TYPE;
+use crate::middleware::Middleware;
+use crate::router::{Handler, ResponseResult};
+use crate::{Body, Method, Response, StatusCode};
+
+/// Latency buckets in seconds used by default
+const DEFAULT_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Route label of the requests without a matched route
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Metrics records the request count, latency histogram and in-flight requests
+///
+/// Requests are labelled by method, status class and route pattern rather than the raw path.
+/// Apply it as pre-routing middleware to also count the unmatched requests.
+///
+/// # Example
+///
+/// ```
+/// # use obsidian::App;
+/// # use obsidian::middleware::Metrics;
+/// let mut app = App::new();
+/// let metrics = Metrics::new();
+///
+/// app.use_pre_routing(metrics.clone());
+/// app.get("/metrics", metrics.handler());
+/// ```
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Arc<Registry>,
+}
+
+struct Registry {
+    buckets: Vec<f64>,
+    series: Mutex<BTreeMap<Labels, Series>>,
+    in_flight: AtomicI64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Labels {
+    route: String,
+    method: &'static str,
+    status: &'static str,
+}
+
+struct Series {
+    count: u64,
+    sum: f64,
+    /// Cumulative count of each bucket
+    buckets: Vec<u64>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics::with_buckets(DEFAULT_BUCKETS.to_vec())
+    }
+
+    /// Latency histogram with the bucket upper bounds in seconds
+    /// Panic if the buckets are empty or not increasing
+    pub fn with_buckets(buckets: Vec<f64>) -> Self {
+        if buckets.is_empty() || buckets.windows(2).any(|pair| pair[0] >= pair[1]) {
+            panic!("ERROR: Metrics buckets must be increasing");
+        }
+
+        Metrics {
+            registry: Arc::new(Registry {
+                buckets,
+                series: Mutex::default(),
+                in_flight: AtomicI64::new(0),
+            }),
+        }
+    }
+
+    /// Handler responding with the metrics in the Prometheus text format
+    pub fn handler(&self) -> impl Handler {
+        let metrics = self.clone();
+
+        move |_ctx: Context| -> ResponseResult {
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, "text/plain; version=0.0.4")
+                .body(Body::from(metrics.render()))
+        }
+    }
+
+    fn observe(&self, labels: Labels, seconds: f64) {
+        let buckets = &self.registry.buckets;
+        let mut series = match self.registry.series.lock() {
+            Ok(series) => series,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let series = series.entry(labels).or_insert_with(|| Series {
+            count: 0,
+            sum: 0.0,
+            buckets: vec![0; buckets.len()],
+        });
+
+        series.count += 1;
+        series.sum += seconds;
+
+        for (count, bound) in series.buckets.iter_mut().zip(buckets.iter()) {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+    }
+
+    /// Metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let series = match self.registry.series.lock() {
+            Ok(series) => series,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let mut output = String::new();
+
+        output.push_str("# HELP http_requests_total Total number of HTTP requests.\n");
+        output.push_str("# TYPE http_requests_total counter\n");
+
+        for (labels, series) in series.iter() {
+            let _ = writeln!(
+                output,
+                "http_requests_total{{{}}} {}",
+                labels.render(),
+                series.count
+            );
+        }
+
+        output.push_str("# HELP http_request_duration_seconds HTTP request latency in seconds.\n");
+        output.push_str("# TYPE http_request_duration_seconds histogram\n");
+
+        for (labels, series) in series.iter() {
+            let labels = labels.render();
+
+            for (count, bound) in series.buckets.iter().zip(self.registry.buckets.iter()) {
+                let _ = writeln!(
+                    output,
+                    "http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, bound, count
+                );
+            }
+
+            let _ = writeln!(
+                output,
+                "http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, series.count
+            );
+            let _ = writeln!(
+                output,
+                "http_request_duration_seconds_sum{{{}}} {}",
+                labels, series.sum
+            );
+            let _ = writeln!(
+                output,
+                "http_request_duration_seconds_count{{{}}} {}",
+                labels, series.count
+            );
+        }
+
+        output.push_str("# HELP http_requests_in_flight Number of HTTP requests in progress.\n");
+        output.push_str("# TYPE http_requests_in_flight gauge\n");
+
+        let _ = writeln!(
+            output,
+            "http_requests_in_flight {}",
+            self.registry.in_flight.load(Ordering::Relaxed)
+        );
+
+        output
+    }
+}
+
+impl Labels {
+    fn render(&self) -> String {
+        format!(
+            "method=\"{}\",route=\"{}\",status=\"{}\"",
+            self.method,
+            escape(&self.route),
+            self.status
+        )
+    }
+}
+
+/// Escape the label value for the text format
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Extension methods share one label so that arbitrary tokens do not create new series
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::HEAD => "HEAD",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::CONNECT => "CONNECT",
+        Method::OPTIONS => "OPTIONS",
+        Method::TRACE => "TRACE",
+        Method::PATCH => "PATCH",
+        _ => "other",
+    }
+}
+
+fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+/// Decrement the in-flight gauge even if the response future is dropped
+struct InFlight(Arc<Registry>);
+
+impl InFlight {
+    fn new(registry: Arc<Registry>) -> Self {
+        registry.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlight(registry)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Middleware for Metrics {
+    fn handle<'a>(
+        &'a self,
+        context: Context,
+        ep_executor: EndpointExecutor<'a>,
+    ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        let start = Instant::now();
+        let in_flight = InFlight::new(self.registry.clone());
+        let method = method_label(context.method());
+        let route = context.route_pattern().map(|route| route.to_string());
+        let metrics = self.clone();
+
+        Box::new(ep_executor.next(context).map(move |res| {
+            // Pre-routing middlewares only know the route once the response is ready
+            let route = route
+                .or_else(|| {
+                    res.extensions()
+                        .get::<RoutePattern>()
+                        .map(|pattern| pattern.0.to_string())
+                })
+                .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+
+            let labels = Labels {
+                route,
+                method,
+                status: status_class(res.status()),
+            };
+
+            metrics.observe(labels, start.elapsed().as_secs_f64());
+            drop(in_flight);
+
+            res
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_render_test() {
+        let metrics = Metrics::with_buckets(vec![0.1, 1.0]);
+
+        let labels = Labels {
+            route: "/users/:id".to_string(),
+            method: "GET",
+            status: "2xx",
+        };
+
+        metrics.observe(labels.clone(), 0.05);
+        metrics.observe(labels, 0.5);
+
+        let _in_flight = InFlight::new(metrics.registry.clone());
+        let output = metrics.render();
+
+        let labels = "method=\"GET\",route=\"/users/:id\",status=\"2xx\"";
+
+        assert!(output.contains(&format!("http_requests_total{{{}}} 2\n", labels)));
+        assert!(output.contains(&format!(
+            "http_request_duration_seconds_bucket{{{},le=\"0.1\"}} 1\n",
+            labels
+        )));
+        assert!(output.contains(&format!(
+            "http_request_duration_seconds_bucket{{{},le=\"1\"}} 2\n",
+            labels
+        )));
+        assert!(output.contains(&format!(
+            "http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} 2\n",
+            labels
+        )));
+        assert!(output.contains(&format!(
+            "http_request_duration_seconds_sum{{{}}} 0.55\n",
+            labels
+        )));
+        assert!(output.contains("http_requests_in_flight 1\n"));
+    }
+
+    #[test]
+    fn metrics_method_label_test() {
+        assert_eq!(method_label(&Method::PATCH), "PATCH");
+        assert_eq!(
+            method_label(&Method::from_bytes(b"FOOBAR").unwrap()),
+            "other"
+        );
+    }
+
+    #[should_panic]
+    #[test]
+    fn metrics_invalid_buckets_test() {
+        Metrics::with_buckets(vec![1.0, 0.5]);
+    }
+}
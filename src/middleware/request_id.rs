@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use futures::Future;
+use uuid::Uuid;
+
+use crate::app::EndpointExecutor;
+use crate::context::{Context, RequestIdValue};
+use crate::header::{HeaderName, HeaderValue};
+use crate::middleware::{Middleware, REQUEST_ID_HEADER};
+use crate::{Body, Response};
+
+/// Longest incoming request ID accepted
+const MAX_REQUEST_ID_LEN: usize = 200;
+
+/// RequestId propagates the incoming request ID or generates a new one
+///
+/// The ID is available by `Context::request_id` and echoed in the response header.
+/// Apply it as pre-routing middleware so that unmatched and rejected requests carry the ID too.
+///
+/// # Example
+///
+/// ```
+/// # use obsidian::App;
+/// # use obsidian::context::Context;
+/// # use obsidian::middleware::{Logger, RequestId};
+/// let mut app = App::new();
+///
+/// app.use_pre_routing(RequestId::new());
+/// app.use_pre_routing(Logger::new());
+/// app.get("/", |ctx: Context| ctx.request_id().unwrap_or_default().to_string());
+/// ```
+pub struct RequestId {
+    header: HeaderName,
+    generator: Box<dyn Fn() -> String + Send + Sync>,
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestId {
+    /// Use the `x-request-id` header and generate UUID v4
+    pub fn new() -> Self {
+        RequestId {
+            header: HeaderName::from_static(REQUEST_ID_HEADER),
+            generator: Box::new(|| Uuid::new_v4().to_string()),
+        }
+    }
+
+    /// Panic if the header name is invalid
+    pub fn header(mut self, header: &str) -> Self {
+        self.header = HeaderName::from_bytes(header.as_bytes())
+            .unwrap_or_else(|_| panic!("ERROR: Invalid request ID header {}", header));
+        self
+    }
+
+    /// Generate the ID of the requests without a valid one, for example a ULID
+    pub fn generator(mut self, generator: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        self.generator = Box::new(generator);
+        self
+    }
+
+    /// Incoming ID if it is printable ASCII of a reasonable length
+    fn incoming(&self, context: &Context) -> Option<HeaderValue> {
+        context
+            .headers()
+            .get(&self.header)
+            .filter(|value| {
+                !value.is_empty()
+                    && value.len() <= MAX_REQUEST_ID_LEN
+                    && value.as_bytes().iter().all(|byte| byte.is_ascii_graphic())
+            })
+            .cloned()
+    }
+}
+
+impl Middleware for RequestId {
+    fn handle<'a>(
+        &'a self,
+        mut context: Context,
+        ep_executor: EndpointExecutor<'a>,
+    ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        let value = self
+            .incoming(&context)
+            .or_else(|| HeaderValue::from_str(&(self.generator)()).ok());
+
+        let value = match value {
+            Some(value) => value,
+            None => return ep_executor.next(context),
+        };
+
+        // Header value is printable ASCII so it is also valid UTF-8
+        let request_id = RequestIdValue(Arc::from(value.to_str().unwrap_or_default()));
+        let header = self.header.clone();
+
+        context.headers_mut().insert(header.clone(), value.clone());
+        context.extensions_mut().insert(request_id.clone());
+
+        Box::new(ep_executor.next(context).map(move |mut res| {
+            res.headers_mut().insert(header, value);
+            res.extensions_mut().insert(request_id);
+            res
+        }))
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use futures::Future;
+use uuid::Uuid;
+
+use crate::app::EndpointExecutor;
+use crate::context::Context;
+use crate::header::{HeaderMap, HeaderName, HeaderValue};
+use crate::middleware::Middleware;
+use crate::{Body, Response};
+
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+pub const TRACESTATE_HEADER: &str = "tracestate";
+
+/// Most list members kept in the tracestate
+const MAX_TRACESTATE_MEMBERS: usize = 32;
+
+/// TraceContext is the W3C trace context of the request
+///
+/// The span ID identifies the current request and the parent ID the caller span, if any.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceContext {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    parent_id: Option<[u8; 8]>,
+    flags: u8,
+    tracestate: Option<String>,
+}
+
+impl TraceContext {
+    /// Start a new sampled trace
+    pub fn new_root() -> Self {
+        let mut trace_id = [0; 16];
+        trace_id.copy_from_slice(Uuid::new_v4().as_bytes());
+
+        TraceContext {
+            trace_id,
+            span_id: new_span_id(),
+            parent_id: None,
+            flags: 0x01,
+            tracestate: None,
+        }
+    }
+
+    /// Trace context of the caller, None if the traceparent is missing or invalid
+    /// An invalid tracestate is dropped while the traceparent is kept
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let mut traceparents = headers.get_all(TRACEPARENT_HEADER).iter();
+        let traceparent = traceparents.next()?.to_str().ok()?;
+
+        if traceparents.next().is_some() {
+            return None;
+        }
+
+        let mut context = TraceContext::parse_traceparent(traceparent)?;
+
+        let tracestate = headers
+            .get_all(TRACESTATE_HEADER)
+            .iter()
+            .map(|value| value.to_str().ok())
+            .collect::<Option<Vec<&str>>>();
+
+        context.tracestate = tracestate.and_then(|values| parse_tracestate(&values.join(",")));
+
+        Some(context)
+    }
+
+    /// Parse `version-trace_id-parent_id-flags`, fields of future versions are ignored
+    pub fn parse_traceparent(traceparent: &str) -> Option<Self> {
+        let traceparent = traceparent.trim_matches(|c| c == ' ' || c == '\t');
+
+        if !traceparent.is_ascii() {
+            return None;
+        }
+
+        let version = parse_hex::<1>(traceparent.get(0..2)?)?[0];
+
+        // Version 00 has exactly 55 characters and later versions may append fields
+        let valid_len = match version {
+            0xff => false,
+            0x00 => traceparent.len() == 55,
+            _ => traceparent.len() == 55 || traceparent.as_bytes().get(55) == Some(&b'-'),
+        };
+
+        if !valid_len {
+            return None;
+        }
+
+        let bytes = traceparent.as_bytes();
+
+        if bytes[2] != b'-' || bytes[35] != b'-' || bytes[52] != b'-' {
+            return None;
+        }
+
+        let trace_id = parse_hex::<16>(&traceparent[3..35])?;
+        let parent_id = parse_hex::<8>(&traceparent[36..52])?;
+        let flags = parse_hex::<1>(&traceparent[53..55])?[0];
+
+        if trace_id == [0; 16] || parent_id == [0; 8] {
+            return None;
+        }
+
+        Some(TraceContext {
+            trace_id,
+            span_id: parent_id,
+            parent_id: None,
+            flags,
+            tracestate: None,
+        })
+    }
+
+    /// Child span of the current span in the same trace
+    /// Only the sampled flag is propagated
+    pub fn child(&self) -> Self {
+        TraceContext {
+            trace_id: self.trace_id,
+            span_id: new_span_id(),
+            parent_id: Some(self.span_id),
+            flags: self.flags & 0x01,
+            tracestate: self.tracestate.clone(),
+        }
+    }
+
+    pub fn trace_id(&self) -> String {
+        to_hex(&self.trace_id)
+    }
+
+    pub fn span_id(&self) -> String {
+        to_hex(&self.span_id)
+    }
+
+    pub fn parent_id(&self) -> Option<String> {
+        self.parent_id.as_ref().map(|parent_id| to_hex(parent_id))
+    }
+
+    pub fn sampled(&self) -> bool {
+        self.flags & 0x01 == 0x01
+    }
+
+    pub fn tracestate(&self) -> Option<&str> {
+        self.tracestate.as_deref()
+    }
+
+    /// Traceparent of the current span in version 00
+    pub fn traceparent(&self) -> String {
+        format!(
+            "00-{}-{}-{:02x}",
+            self.trace_id(),
+            self.span_id(),
+            self.flags
+        )
+    }
+
+    /// Add the traceparent and tracestate headers, for example to an outgoing request
+    pub fn inject(&self, headers: &mut HeaderMap) {
+        // Traceparent and tracestate only contain visible ASCII
+        if let Ok(traceparent) = HeaderValue::from_str(&self.traceparent()) {
+            headers.insert(HeaderName::from_static(TRACEPARENT_HEADER), traceparent);
+        }
+
+        if let Some(tracestate) = self
+            .tracestate()
+            .and_then(|tracestate| HeaderValue::from_str(tracestate).ok())
+        {
+            headers.insert(HeaderName::from_static(TRACESTATE_HEADER), tracestate);
+        }
+    }
+}
+
+fn new_span_id() -> [u8; 8] {
+    let mut span_id = [0; 8];
+
+    while span_id == [0; 8] {
+        span_id.copy_from_slice(&Uuid::new_v4().as_bytes()[..8]);
+    }
+
+    span_id
+}
+
+/// Decode lowercase hex, uppercase is invalid in the trace context
+fn parse_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    if hex.len() != N * 2 {
+        return None;
+    }
+
+    let mut bytes = [0; N];
+
+    for (byte, pair) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let digit = |c: u8| match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            _ => None,
+        };
+
+        *byte = digit(pair[0])? << 4 | digit(pair[1])?;
+    }
+
+    Some(bytes)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+
+    for byte in bytes {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+
+    hex
+}
+
+/// Normalized tracestate, None if it is empty or any list member is invalid
+fn parse_tracestate(tracestate: &str) -> Option<String> {
+    let mut keys = HashSet::new();
+    let mut members = vec![];
+
+    for member in tracestate.split(',') {
+        let member = member.trim_matches(|c| c == ' ' || c == '\t');
+
+        if member.is_empty() {
+            continue;
+        }
+
+        let (key, value) = member.split_once('=')?;
+
+        if !is_valid_key(key) || !is_valid_value(value) || !keys.insert(key) {
+            return None;
+        }
+
+        members.push(member);
+    }
+
+    if members.is_empty() || members.len() > MAX_TRACESTATE_MEMBERS {
+        return None;
+    }
+
+    Some(members.join(","))
+}
+
+/// Simple key or `tenant@system` multi-tenant key
+fn is_valid_key(key: &str) -> bool {
+    let is_key_char = |c: u8| matches!(c, b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'*' | b'/');
+
+    match key.split_once('@') {
+        Some((tenant, system)) => {
+            !tenant.is_empty()
+                && tenant.len() <= 241
+                && tenant.bytes().all(is_key_char)
+                && system.len() <= 14
+                && system
+                    .bytes()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_lowercase())
+                && system.bytes().all(is_key_char)
+        }
+        None => {
+            key.len() <= 256
+                && key.bytes().next().is_some_and(|c| c.is_ascii_lowercase())
+                && key.bytes().all(is_key_char)
+        }
+    }
+}
+
+/// Printable ASCII except `,` and `=` without the trailing space
+fn is_valid_value(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 256
+        && !value.ends_with(' ')
+        && value
+            .bytes()
+            .all(|c| (0x20..=0x7e).contains(&c) && c != b',' && c != b'=')
+}
+
+/// TraceContextMiddleware continues the trace of the caller or starts a new one
+///
+/// The trace context of the request is available by `Context::trace_context`
+/// and its traceparent is added to the response headers.
+///
+/// # Example
+///
+/// ```
+/// # use obsidian::App;
+/// # use obsidian::context::Context;
+/// # use obsidian::middleware::TraceContextMiddleware;
+/// let mut app = App::new();
+///
+/// app.use_pre_routing(TraceContextMiddleware::new());
+/// app.get("/", |ctx: Context| {
+///     // Propagate to the outgoing requests
+///     let mut headers = obsidian::HeaderMap::new();
+///     ctx.trace_context().unwrap().inject(&mut headers);
+///
+///     "traced"
+/// });
+/// ```
+#[derive(Default)]
+pub struct TraceContextMiddleware {}
+
+impl TraceContextMiddleware {
+    pub fn new() -> Self {
+        TraceContextMiddleware {}
+    }
+}
+
+impl Middleware for TraceContextMiddleware {
+    fn handle<'a>(
+        &'a self,
+        mut context: Context,
+        ep_executor: EndpointExecutor<'a>,
+    ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        let trace_context = match TraceContext::from_headers(context.headers()) {
+            Some(parent) => parent.child(),
+            None => TraceContext::new_root(),
+        };
+
+        context.extensions_mut().insert(trace_context.clone());
+
+        Box::new(ep_executor.next(context).map(move |mut res| {
+            trace_context.inject(res.headers_mut());
+            res.extensions_mut().insert(trace_context);
+            res
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACEPARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+    #[test]
+    fn parse_traceparent_test() {
+        let context = TraceContext::parse_traceparent(TRACEPARENT).unwrap();
+
+        assert_eq!(context.trace_id(), "0af7651916cd43dd8448eb211c80319c");
+        assert_eq!(context.span_id(), "b7ad6b7169203331");
+        assert!(context.sampled());
+        assert_eq!(context.traceparent(), TRACEPARENT);
+
+        let future_version = "cc-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01-extra";
+        assert!(TraceContext::parse_traceparent(future_version).is_some());
+
+        let invalid_cases = [
+            "",
+            "ff-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+            "00-0AF7651916CD43DD8448EB211C80319C-b7ad6b7169203331-01",
+            "00-00000000000000000000000000000000-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-0000000000000000-01",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01-extra",
+            "00-0af7651916cd43dd8448eb211c80319c_b7ad6b7169203331-01",
+            "cc-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01extra",
+        ];
+
+        for case in invalid_cases.iter() {
+            assert!(TraceContext::parse_traceparent(case).is_none(), "{}", case);
+        }
+    }
+
+    #[test]
+    fn child_test() {
+        let parent = TraceContext::parse_traceparent(TRACEPARENT).unwrap();
+        let child = parent.child();
+
+        assert_eq!(child.trace_id(), parent.trace_id());
+        assert_eq!(child.parent_id().unwrap(), parent.span_id());
+        assert_ne!(child.span_id(), parent.span_id());
+        assert_eq!(child.traceparent().len(), 55);
+    }
+
+    #[test]
+    fn tracestate_test() {
+        let mut headers = HeaderMap::new();
+        headers.insert(TRACEPARENT_HEADER, HeaderValue::from_static(TRACEPARENT));
+        headers.append(
+            TRACESTATE_HEADER,
+            HeaderValue::from_static("rojo=00f067aa0ba902b7"),
+        );
+        headers.append(
+            TRACESTATE_HEADER,
+            HeaderValue::from_static(" , tenant@vendor=t61rcWkgMzE "),
+        );
+
+        let context = TraceContext::from_headers(&headers).unwrap();
+
+        assert_eq!(
+            context.tracestate().unwrap(),
+            "rojo=00f067aa0ba902b7,tenant@vendor=t61rcWkgMzE"
+        );
+
+        let invalid_cases = ["rojo", "Rojo=1", "rojo=1,rojo=2", "rojo=a=b", "rojo="];
+
+        for case in invalid_cases.iter() {
+            assert!(parse_tracestate(case).is_none(), "{}", case);
+        }
+    }
+}
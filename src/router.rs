@@ -1,23 +1,59 @@
+pub mod guard;
 mod handler;
+mod host_pattern;
+mod mount;
+mod path;
 mod req_deserializer;
 mod resource;
+mod resource_builder;
+mod resource_controller;
 mod responder;
 pub mod response;
 mod response_body;
 mod route;
+mod route_builder;
+mod route_matcher;
+mod route_names;
+mod route_service;
+mod route_table;
 mod route_trie;
+mod rules;
+mod trailing_slash;
+mod versioning;
 
+use self::resource::any_method;
 use self::route_trie::{RouteTrie, RouteValueResult};
+use std::fmt::Display;
+use std::sync::Arc;
+
+use crate::app::{Endpoint, EndpointExecutor};
 use crate::context::Context;
-use crate::middleware::Middleware;
-use crate::Method;
+use crate::middleware::{FnMiddleware, Hook, HookMiddleware, Middleware};
+use crate::{Body, Method, ObsidianError, Request, Response, StatusCode};
+use futures::Future;
+use hyper::service::Service;
 
+pub use self::guard::Guard;
 pub use self::handler::Handler;
+pub use self::host_pattern::HostPattern;
+pub use self::mount::Mount;
+pub use self::path::{is_normalized_path, normalize_path};
 pub use self::req_deserializer::{from_cow_map, Error as FormError};
-pub use self::resource::Resource;
+pub use self::resource::{PathResources, Resource};
+pub use self::resource_builder::ResourceBuilder;
+pub use self::resource_controller::ResourceController;
 pub use self::responder::{Responder, ResponseResult};
 pub use self::response_body::ResponseBody;
 pub use self::route::Route;
+pub(crate) use self::route::RouteChain;
+pub use self::route_builder::RouteBuilder;
+pub use self::route_matcher::{RouteMatch, RouteMatcher};
+pub use self::route_names::RouteNames;
+pub use self::route_service::RouteService;
+pub use self::route_table::{RouteInfo, RouteTable};
+pub use self::rules::{PathPattern, Redirect, RewriteRule, Rules};
+pub use self::trailing_slash::TrailingSlash;
+pub use self::versioning::{Deprecation, VersionGuard, VersionStrategy, Versioning};
 
 pub struct Router {
     routes: RouteTrie,
@@ -44,20 +80,57 @@ impl Router {
         }
     }
 
-    pub fn get(&mut self, path: &str, handler: impl Handler) {
-        self.insert_route(Method::GET, path, handler);
+    pub fn get(&mut self, path: &str, handler: impl Handler) -> RouteBuilder<'_> {
+        self.insert_route(Method::GET, path, handler)
+    }
+
+    pub fn post(&mut self, path: &str, handler: impl Handler) -> RouteBuilder<'_> {
+        self.insert_route(Method::POST, path, handler)
+    }
+
+    pub fn put(&mut self, path: &str, handler: impl Handler) -> RouteBuilder<'_> {
+        self.insert_route(Method::PUT, path, handler)
+    }
+
+    pub fn delete(&mut self, path: &str, handler: impl Handler) -> RouteBuilder<'_> {
+        self.insert_route(Method::DELETE, path, handler)
     }
 
-    pub fn post(&mut self, path: &str, handler: impl Handler) {
-        self.insert_route(Method::POST, path, handler);
+    pub fn patch(&mut self, path: &str, handler: impl Handler) -> RouteBuilder<'_> {
+        self.insert_route(Method::PATCH, path, handler)
     }
 
-    pub fn put(&mut self, path: &str, handler: impl Handler) {
-        self.insert_route(Method::PUT, path, handler);
+    /// Register the routes declared by the route attributes
+    pub fn service(&mut self, service: impl RouteService) {
+        service.register(self);
     }
 
-    pub fn delete(&mut self, path: &str, handler: impl Handler) {
-        self.insert_route(Method::DELETE, path, handler);
+    /// Register the REST routes of the controller under the path by the actions of the builder
+    /// Member routes take the resource identity as the param `id`
+    pub fn resource<C: ResourceController>(
+        &mut self,
+        path: &str,
+        controller: C,
+    ) -> ResourceBuilder<'_, C> {
+        ResourceBuilder::new(self, path, controller)
+    }
+
+    /// Set the policy for request paths which differ from the route only by a trailing slash
+    /// Panic if the redirect status is neither 301 Moved Permanently nor 308 Permanent Redirect
+    pub fn trailing_slash(&mut self, policy: TrailingSlash) {
+        if let TrailingSlash::Redirect(status) = policy {
+            if !matches!(
+                status,
+                StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT
+            ) {
+                panic!(
+                    "Trailing slash redirect requires 301 or 308 status, found {}",
+                    status
+                );
+            }
+        }
+
+        self.routes.set_trailing_slash(policy);
     }
 
     /// Apply middleware in the provided route
@@ -70,6 +143,35 @@ impl Router {
         self.routes.insert_default_middleware(middleware);
     }
 
+    /// Apply the closure as middleware in the provided route
+    pub fn use_fn_to<F, R>(&mut self, path: &str, f: F)
+    where
+        F: Fn(Context, EndpointExecutor<'_>) -> R + Send + Sync + 'static,
+        R: Future<Item = Response<Body>, Error = hyper::Error> + Send + 'static,
+    {
+        self.use_service_to(path, FnMiddleware::new(f));
+    }
+
+    /// Apply the closure as middleware in current relative route
+    /// The closure receives the context and the executor of the rest of the chain
+    pub fn use_fn<F, R>(&mut self, f: F)
+    where
+        F: Fn(Context, EndpointExecutor<'_>) -> R + Send + Sync + 'static,
+        R: Future<Item = Response<Body>, Error = hyper::Error> + Send + 'static,
+    {
+        self.use_service(FnMiddleware::new(f));
+    }
+
+    /// Apply the hook in the provided route
+    pub fn use_hook_to(&mut self, path: &str, hook: impl Hook) {
+        self.use_service_to(path, HookMiddleware::new(hook));
+    }
+
+    /// Apply the hook in current relative route
+    pub fn use_hook(&mut self, hook: impl Hook) {
+        self.use_service(HookMiddleware::new(hook));
+    }
+
     /// Serve static files by the virtual path as the route and directory path as the server file path
     pub fn use_static_to(&mut self, virtual_path: &str, dir_path: &str) {
         let mut path = String::from(virtual_path);
@@ -94,14 +196,165 @@ impl Router {
         RouteTrie::insert_sub_route(&mut self.routes, path, other.routes);
     }
 
+    /// Group routes and middlewares under the shared path prefix
+    ///
+    /// Routes of the scope are merged into the parent router, so a scope can
+    /// extend the nodes which already contain routes or middlewares.
+    /// Middlewares of the scope only apply to the routes registered in the scope.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use obsidian::router::Router;
+    /// # use obsidian::middleware::Logger;
+    /// let mut router = Router::new();
+    ///
+    /// router.scope("/api/v1", |r| {
+    ///     r.use_service(Logger::new());
+    ///     r.get("/users", |_ctx| "users");
+    ///
+    ///     r.scope("/admin", |r| {
+    ///         r.get("/stats", |_ctx| "stats");
+    ///     });
+    /// });
+    ///
+    /// assert!(router.search_route("/api/v1/admin/stats").is_some());
+    /// ```
+    pub fn scope(&mut self, prefix: &str, f: impl FnOnce(&mut Router)) {
+        let mut router = Router::new();
+        f(&mut router);
+
+        // Middlewares of the scope must not reach the routes registered outside of it
+        router.routes.move_middlewares_to_routes();
+        self.use_router(prefix, router);
+    }
+
+    /// Redirect the requests of the source pattern to the target filled with the captured params
+    /// For example `/old/:id` to `/new/:id`, `*` refers to the rest of the path
+    /// Panic if the status is not a redirection or the target refers to params not in the source
+    pub fn redirect(&mut self, from: &str, to: &str, status: StatusCode) {
+        self.insert_redirect(from, Redirect::new(from, to, status));
+    }
+
+    pub(crate) fn insert_redirect(&mut self, from: &str, redirect: Redirect) {
+        let redirect = Endpoint::Handler(Arc::new(redirect));
+
+        self.routes
+            .insert_route(from, Route::with_endpoint(any_method(), redirect));
+    }
+
+    /// Forward every request under the path to the hyper service with the path stripped
+    /// Each request is served by a clone of the service
+    /// Middlewares of the path still apply before the service
+    pub fn mount_service<S>(&mut self, path: &str, service: S)
+    where
+        S: Service<ReqBody = Body, ResBody = Body> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+    {
+        self.mount(path, Mount::service(service));
+    }
+
+    /// Forward every request under the path to the function with the path stripped
+    /// Middlewares of the path still apply before the function
+    pub fn mount_fn<F, R>(&mut self, path: &str, f: F)
+    where
+        F: Fn(Request<Body>) -> R + Send + Sync + 'static,
+        R: Future<Item = Response<Body>, Error = hyper::Error> + Send + 'static,
+    {
+        self.mount(path, Mount::function(f));
+    }
+
+    /// Requests of every method without a route of its own under the path reach the mount
+    fn mount(&mut self, path: &str, mount: Mount) {
+        let mount = Endpoint::Mount(Arc::new(mount));
+        let wildcard = format!("{}/*", path.trim_end_matches('/'));
+
+        // Wildcard does not match the root path itself
+        if path.split('/').all(|key| key.is_empty()) {
+            self.routes
+                .insert_route("/", Route::with_endpoint(any_method(), mount.clone()));
+        }
+
+        self.routes
+            .insert_route(&wildcard, Route::with_endpoint(any_method(), mount));
+    }
+
+    /// Register the routes of the API version selected by the versioning strategy
+    /// Middlewares applied in the closure only apply to the routes of the version
+    pub fn version(&mut self, versioning: &Versioning, version: u32, f: impl FnOnce(&mut Router)) {
+        let mut router = Router::new();
+        f(&mut router);
+
+        // Path middlewares would otherwise be shared with the other versions on merge
+        router.routes.move_middlewares_to_routes();
+
+        let guard = versioning
+            .guard(version)
+            .map(|guard| Arc::new(guard) as Arc<dyn Guard>);
+        let deprecation = versioning
+            .deprecation(version)
+            .map(|deprecation| Arc::new(deprecation) as Arc<dyn Middleware>);
+
+        router.routes.for_each_route_mut(|route| {
+            if let Some(guard) = &guard {
+                route.guards.insert(0, guard.clone());
+            }
+
+            if let Some(deprecation) = &deprecation {
+                route.middlewares.push(deprecation.clone());
+            }
+        });
+
+        if *versioning.strategy() == VersionStrategy::Prefix && versioning.is_default(version) {
+            let mut alias = router.clone();
+
+            // Names refer to the prefixed routes and the root path stays with the app
+            alias.routes.for_each_route_mut(|route| route.name = None);
+            alias.routes.remove_root();
+            self.use_router("/", alias);
+        }
+
+        self.use_router(&versioning.prefix(version), router);
+    }
+
+    /// Compile the routes into the read-only matcher used to serve the requests
+    pub fn freeze(&self) -> RouteMatcher {
+        RouteMatcher::new(self.routes.route_values(), self.routes.trailing_slash())
+    }
+
     pub fn search_route(&self, path: &str) -> Option<RouteValueResult> {
         self.routes.search_route(path)
     }
 
-    fn insert_route(&mut self, method: Method, path: &str, handler: impl Handler) {
+    /// Build the path of the named route with the percent-encoded params
+    pub fn url_for<I, K, V>(&self, name: &str, params: I) -> Result<String, ObsidianError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Display,
+    {
+        self.routes.route_names().url_for(name, params)
+    }
+
+    /// Names of all routes including the mounted sub routers
+    pub fn route_names(&self) -> RouteNames {
+        self.routes.route_names()
+    }
+
+    /// Describe all registered routes including the mounted sub routers
+    pub fn routes(&self) -> RouteTable {
+        self.routes.route_table()
+    }
+
+    fn insert_route(
+        &mut self,
+        method: Method,
+        path: &str,
+        handler: impl Handler,
+    ) -> RouteBuilder<'_> {
         let route = Route::new(method, handler);
 
-        self.routes.insert_route(path, route);
+        RouteBuilder::new(self.routes.insert_route(path, route))
     }
 
     fn static_virtual_file_handler(virtual_path: &str, dir_path: &str) -> impl Handler {
@@ -118,34 +371,38 @@ impl Router {
 
         move |ctx: Context| {
             let mut dir_path = dir_path.clone();
-            let mut relative_path = ctx
-                .uri()
-                .path()
-                .split('/')
-                .filter(|key| !key.is_empty())
-                .skip(virtual_path_len)
-                .map(|x| x.to_string())
-                .collect::<Vec<String>>();
+            let relative_path = match Self::static_relative_path(ctx.uri().path()) {
+                Some(relative_path) => relative_path,
+                None => return StatusCode::NOT_FOUND.respond_to(),
+            };
 
-            dir_path.append(&mut relative_path);
+            dir_path.extend(relative_path.into_iter().skip(virtual_path_len));
 
             response::file(&dir_path.join("/"))
         }
     }
 
     fn static_dir_file_handler() -> impl Handler {
-        move |ctx: Context| {
-            let relative_path = ctx
-                .uri()
-                .path()
-                .split('/')
-                .filter(|key| !key.is_empty())
-                .map(|x| x.to_string())
-                .collect::<Vec<String>>();
-
-            response::file(&relative_path.join("/"))
+        move |ctx: Context| match Self::static_relative_path(ctx.uri().path()) {
+            Some(relative_path) => response::file(&relative_path.join("/")),
+            None => StatusCode::NOT_FOUND.respond_to(),
         }
     }
+
+    /// Normalized segments of the requested file
+    /// None if any segment would escape the served directory
+    fn static_relative_path(path: &str) -> Option<Vec<String>> {
+        let segments = normalize_path(path).ok()?;
+
+        if segments
+            .iter()
+            .any(|segment| segment.contains('/') || segment.contains('\\'))
+        {
+            return None;
+        }
+
+        Some(segments)
+    }
 }
 
 #[cfg(test)]
@@ -179,7 +436,7 @@ mod tests {
                 assert_eq!(route_value.method, Method::GET);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
     }
@@ -205,7 +462,7 @@ mod tests {
                 assert_eq!(route_value.method, Method::POST);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
     }
@@ -231,7 +488,7 @@ mod tests {
                 assert_eq!(route_value.method, Method::PUT);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
     }
@@ -257,7 +514,7 @@ mod tests {
                 assert_eq!(route_value.method, Method::DELETE);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
     }
@@ -282,7 +539,7 @@ mod tests {
                 assert_eq!(middlewares.len(), 1);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
     }
@@ -307,7 +564,7 @@ mod tests {
                 assert_eq!(middlewares.len(), 1);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
     }
@@ -355,7 +612,7 @@ mod tests {
                 assert_eq!(route_value.method, Method::DELETE);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
 
@@ -368,7 +625,7 @@ mod tests {
                 assert_eq!(route_value.method, Method::GET);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
     }
@@ -404,7 +661,7 @@ mod tests {
                 assert_eq!(route_value.method, Method::GET);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
 
@@ -417,11 +674,248 @@ mod tests {
                 assert_eq!(route_value.method, Method::GET);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
     }
 
+    #[test]
+    fn router_url_for_test() {
+        let mut main_router = Router::new();
+        let mut sub_router = Router::new();
+
+        main_router.get("/", handler).name("index");
+        sub_router.get("/users/:id", handler).name("user.show");
+        sub_router.get("/", handler).name("user.index");
+
+        main_router.use_router("/api/v1", sub_router);
+
+        let empty: Vec<(&str, i32)> = vec![];
+
+        assert_eq!(main_router.url_for("index", empty.clone()).unwrap(), "/");
+        assert_eq!(main_router.url_for("user.index", empty).unwrap(), "/api/v1");
+        assert_eq!(
+            main_router
+                .url_for("user.show", vec![("id", "John Doe")])
+                .unwrap(),
+            "/api/v1/users/John%20Doe"
+        );
+
+        let result = main_router
+            .search_route("/api/v1/users/John%20Doe")
+            .unwrap();
+        let route = result.get_route(&Method::GET).unwrap();
+
+        assert_eq!(route.path, "/api/v1/users/:id");
+        assert_eq!(route.name, Some("user.show".to_string()));
+    }
+
+    #[test]
+    fn router_routes_test() {
+        let mut main_router = Router::new();
+        let mut sub_router = Router::new();
+
+        main_router.use_service(Logger::new());
+        main_router.get("/", handler).name("index");
+        sub_router.get("/users/:id", handler).name("user.show");
+        sub_router.post("/users/:id", handler);
+        sub_router.use_service_to("/users", Logger::new());
+
+        main_router.use_router("/api", sub_router);
+
+        let routes = main_router.routes();
+        let logger = std::any::type_name::<Logger>();
+
+        assert_eq!(
+            routes.into_iter().collect::<Vec<RouteInfo>>(),
+            vec![
+                RouteInfo {
+                    method: Method::GET,
+                    path: "/".to_string(),
+                    name: Some("index".to_string()),
+                    middlewares: vec![logger],
+                },
+                RouteInfo {
+                    method: Method::GET,
+                    path: "/api/users/:id".to_string(),
+                    name: Some("user.show".to_string()),
+                    middlewares: vec![logger, logger],
+                },
+                RouteInfo {
+                    method: Method::POST,
+                    path: "/api/users/:id".to_string(),
+                    name: None,
+                    middlewares: vec![logger, logger],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn router_route_middleware_test() {
+        let mut router = Router::new();
+
+        router.get("/admin/users", handler);
+        router.post("/admin/users", handler).with(Logger::new());
+
+        let result = router.search_route("/admin/users").unwrap();
+        let get_route = result.get_route(&Method::GET).unwrap();
+        let post_route = result.get_route(&Method::POST).unwrap();
+
+        assert_eq!(result.get_middlewares().len(), 0);
+        assert_eq!(get_route.middlewares.len(), 0);
+        assert_eq!(post_route.middlewares.len(), 1);
+
+        let middlewares = router
+            .routes()
+            .iter()
+            .map(|route| route.middlewares.len())
+            .collect::<Vec<usize>>();
+
+        assert_eq!(middlewares, vec![0, 1]);
+    }
+
+    #[test]
+    fn router_scope_test() {
+        let mut router = Router::new();
+
+        router.get("/api/health", handler);
+        router.get("/api", handler);
+
+        router.scope("/api", |r| {
+            r.use_service(Logger::new());
+            r.post("/", handler);
+
+            r.scope("/v1/", |r| {
+                r.use_service(Logger::new());
+                r.get("/users/:id", handler).name("user.show");
+            });
+        });
+
+        // Routes registered outside of the scope are not affected by its middlewares
+        let middlewares = router
+            .routes()
+            .iter()
+            .map(|route| {
+                (
+                    route.method.clone(),
+                    route.path.clone(),
+                    route.middlewares.len(),
+                )
+            })
+            .collect::<Vec<(Method, String, usize)>>();
+
+        assert_eq!(
+            middlewares,
+            vec![
+                (Method::GET, "/api".to_string(), 0),
+                (Method::POST, "/api".to_string(), 1),
+                (Method::GET, "/api/health".to_string(), 0),
+                (Method::GET, "/api/v1/users/:id".to_string(), 2),
+            ]
+        );
+
+        assert_eq!(
+            router.url_for("user.show", vec![("id", 1)]).unwrap(),
+            "/api/v1/users/1"
+        );
+    }
+
+    #[test]
+    fn router_scope_router_api_test() {
+        let versioning = Versioning::new(VersionStrategy::Prefix);
+        let mut router = Router::new();
+
+        router.scope("/api", |r| {
+            r.use_fn(|ctx, executor| executor.next(ctx));
+            r.redirect("/old/:id", "/api/users/:id", StatusCode::MOVED_PERMANENTLY);
+            r.version(&versioning, 1, |r| {
+                r.get("/users/:id", handler);
+            });
+        });
+
+        let routes = router
+            .routes()
+            .iter()
+            .map(|route| {
+                (
+                    route.method.to_string(),
+                    route.path.clone(),
+                    route.middlewares.len(),
+                )
+            })
+            .collect::<Vec<(String, String, usize)>>();
+
+        assert_eq!(
+            routes,
+            vec![
+                ("*".to_string(), "/api/old/:id".to_string(), 1),
+                ("GET".to_string(), "/api/v1/users/:id".to_string(), 1),
+            ]
+        );
+    }
+
+    struct PostsController;
+
+    impl ResourceController for PostsController {}
+
+    #[test]
+    fn router_resource_path_test() {
+        let mut router = Router::new();
+
+        router
+            .resource("/posts/", PostsController)
+            .index()
+            .create()
+            .destroy();
+
+        let routes = router
+            .routes()
+            .iter()
+            .map(|route| (route.method.clone(), route.path.clone()))
+            .collect::<Vec<(Method, String)>>();
+
+        // Collection and member routes share the path without the trailing slash
+        assert_eq!(
+            routes,
+            vec![
+                (Method::GET, "/posts".to_string()),
+                (Method::POST, "/posts".to_string()),
+                (Method::DELETE, "/posts/:id".to_string()),
+            ]
+        );
+
+        let mut router = Router::new();
+
+        router.resource("/", PostsController).index().show();
+
+        assert!(router.search_route("/").is_some());
+        assert!(router.search_route("/1").is_some());
+    }
+
+    #[test]
+    fn router_root_path_middleware_test() {
+        let mut router = Router::new();
+
+        router.get("/", handler);
+        router.use_service_to("/", Logger::new());
+
+        let result = router.search_route("/").unwrap();
+
+        assert_eq!(result.get_middlewares().len(), 1);
+    }
+
+    #[should_panic]
+    #[test]
+    fn router_duplicate_name_test() {
+        let mut router = Router::new();
+
+        router.get("/users/:id", handler).name("user.show");
+        router.get("/members/:id", handler).name("user.show");
+
+        router.route_names();
+    }
+
     #[should_panic]
     #[test]
     fn router_duplicate_path_test() {
@@ -431,6 +925,58 @@ mod tests {
         router.get("router/test", handler);
     }
 
+    #[test]
+    fn router_guarded_path_test() {
+        let mut router = Router::new();
+
+        router
+            .get("router/test", handler)
+            .guard(guard::query("debug"));
+        router.get("router/test", handler);
+
+        let result = router.search_route("router/test").unwrap();
+
+        assert_eq!(result.get_routes(&Method::GET).count(), 2);
+        assert_eq!(router.routes().len(), 2);
+    }
+
+    #[should_panic]
+    #[test]
+    fn router_unreachable_guarded_path_test() {
+        let mut router = Router::new();
+
+        router.get("router/test", handler);
+        router
+            .get("router/test", handler)
+            .guard(guard::query("debug"));
+    }
+
+    #[crate::get("/articles/:id", name = "article.show")]
+    fn show_article(_ctx: Context) -> impl Responder {
+        "article"
+    }
+
+    #[crate::patch("/articles/:id")]
+    fn patch_article(_ctx: Context) -> impl Responder {
+        "patched"
+    }
+
+    #[test]
+    fn router_service_test() {
+        let mut router = Router::new();
+
+        router.service(crate::routes![show_article, patch_article]);
+
+        let result = router.search_route("/articles/1").unwrap();
+
+        assert!(result.get_route(&Method::GET).is_some());
+        assert!(result.get_route(&Method::PATCH).is_some());
+        assert_eq!(
+            router.url_for("article.show", vec![("id", 1)]).unwrap(),
+            "/articles/1"
+        );
+    }
+
     #[should_panic]
     #[test]
     fn router_ambiguous_path_test() {
@@ -440,6 +986,101 @@ mod tests {
         router.get("router/test", handler);
     }
 
+    #[should_panic]
+    #[test]
+    fn router_trailing_slash_invalid_redirect_test() {
+        let mut router = Router::new();
+
+        router.trailing_slash(TrailingSlash::Redirect(StatusCode::OK));
+    }
+
+    #[should_panic]
+    #[test]
+    fn router_trailing_slash_not_modified_test() {
+        let mut router = Router::new();
+
+        router.trailing_slash(TrailingSlash::Redirect(StatusCode::NOT_MODIFIED));
+    }
+
+    #[test]
+    fn router_merge_non_empty_test() {
+        let mut main_router = Router::new();
+        let mut users_router = Router::new();
+        let mut orders_router = Router::new();
+
+        main_router.get("/api", handler);
+        main_router.get("/api/users", handler);
+        main_router.use_service_to("/api", Logger::new());
+
+        users_router.post("/users", handler);
+        users_router.get("/users/:id", handler);
+        users_router.use_service(Logger::new());
+
+        orders_router.get("/orders", handler);
+        orders_router.post("/", handler);
+
+        main_router.use_router("/api", users_router);
+        main_router.use_router("/api", orders_router);
+
+        let test_cases = [
+            ("/api", Method::GET, 2),
+            ("/api", Method::POST, 2),
+            ("/api/users", Method::GET, 2),
+            ("/api/users", Method::POST, 2),
+            ("/api/users/1", Method::GET, 2),
+            ("/api/orders", Method::GET, 2),
+        ];
+
+        for case in test_cases.iter() {
+            match main_router.search_route(case.0) {
+                Some(route) => {
+                    assert_eq!(route.get_middlewares().len(), case.2);
+                    assert!(route.get_route(&case.1).is_some());
+                }
+                _ => {
+                    unreachable!();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn router_merge_root_test() {
+        let mut main_router = Router::new();
+        let mut sub_router = Router::new();
+
+        main_router.get("/", handler);
+        main_router.get("/users", handler);
+        main_router.use_service(Logger::new());
+
+        sub_router.post("/", handler);
+        sub_router.get("/orders", handler);
+        sub_router.use_service(Logger::new());
+
+        main_router.use_router("/", sub_router);
+
+        let root = main_router.search_route("/").unwrap();
+
+        assert!(root.get_route(&Method::GET).is_some());
+        assert!(root.get_route(&Method::POST).is_some());
+        assert_eq!(root.get_middlewares().len(), 2);
+        assert!(main_router.search_route("/users").is_some());
+        assert!(main_router.search_route("/orders").is_some());
+    }
+
+    #[should_panic(expected = "Duplicated route method 'GET' at '/api/users' detected")]
+    #[test]
+    fn router_merge_collision_test() {
+        let mut main_router = Router::new();
+        let mut sub_router = Router::new();
+
+        main_router.get("/api/users", handler);
+        sub_router.post("/users", handler);
+        sub_router.get("/users", handler);
+
+        main_router.use_router("/api", sub_router);
+    }
+
     #[should_panic]
     #[test]
     fn router_duplicate_merge_test() {
@@ -455,4 +1096,37 @@ mod tests {
 
         main_router.use_router("sub_router", sub_router);
     }
+
+    #[test]
+    fn router_version_middleware_test() {
+        let versioning = Versioning::new(VersionStrategy::Prefix).default_version(2);
+        let mut router = Router::new();
+
+        router.get("/health", handler);
+        router.version(&versioning, 1, |r| {
+            r.get("/users", handler);
+        });
+        router.version(&versioning, 2, |r| {
+            r.use_service(Logger::new());
+            r.get("/", handler);
+            r.get("/users", handler);
+        });
+
+        let middlewares = router
+            .routes()
+            .iter()
+            .map(|route| (route.path.clone(), route.middlewares.len()))
+            .collect::<Vec<(String, usize)>>();
+
+        assert_eq!(
+            middlewares,
+            vec![
+                ("/health".to_string(), 0),
+                ("/users".to_string(), 1),
+                ("/v1/users".to_string(), 0),
+                ("/v2".to_string(), 1),
+                ("/v2/users".to_string(), 1),
+            ]
+        );
+    }
 }
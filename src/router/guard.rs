@@ -0,0 +1,231 @@
+use hyper::http::request::Parts;
+use url::form_urlencoded;
+
+use crate::header::{HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use crate::StatusCode;
+
+/// Guard decides whether the route accepts the request beyond its path and method
+///
+/// When a guard rejects the request, the next route registered for the same path
+/// and method is tried. The status of the rejection is returned if no route accepts it.
+pub trait Guard: Send + Sync + 'static {
+    fn check(&self, req: &Parts) -> bool;
+
+    /// Status returned when no route accepts the request
+    fn rejection(&self) -> StatusCode {
+        StatusCode::NOT_FOUND
+    }
+}
+
+impl<T> Guard for T
+where
+    T: Fn(&Parts) -> bool + Send + Sync + 'static,
+{
+    fn check(&self, req: &Parts) -> bool {
+        (self)(req)
+    }
+}
+
+/// Match if the media type of `Content-Type` equals the provided one
+/// Rejected with 415 Unsupported Media Type
+pub fn content_type(media_type: &str) -> ContentType {
+    ContentType {
+        media_type: media_type.to_ascii_lowercase(),
+    }
+}
+
+/// Match if `Accept` includes the provided media type, a missing header accepts all
+/// Rejected with 406 Not Acceptable
+pub fn accept(media_type: &str) -> Accept {
+    Accept {
+        media_type: media_type.to_ascii_lowercase(),
+    }
+}
+
+/// Match if the header equals the provided value
+/// Panic if the name or the value is not a valid header
+pub fn header(name: &str, value: &str) -> Header {
+    Header {
+        name: HeaderName::from_bytes(name.as_bytes()).expect("Invalid header name for guard"),
+        value: HeaderValue::from_str(value).expect("Invalid header value for guard"),
+    }
+}
+
+/// Match if the query string contains the key
+pub fn query(key: &str) -> Query {
+    Query {
+        key: key.to_string(),
+    }
+}
+
+pub struct ContentType {
+    media_type: String,
+}
+
+impl Guard for ContentType {
+    fn check(&self, req: &Parts) -> bool {
+        req.headers
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| {
+                media_type_essence(content_type).eq_ignore_ascii_case(&self.media_type)
+            })
+    }
+
+    fn rejection(&self) -> StatusCode {
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    }
+}
+
+pub struct Accept {
+    media_type: String,
+}
+
+impl Guard for Accept {
+    fn check(&self, req: &Parts) -> bool {
+        let accept = match req.headers.get(ACCEPT) {
+            Some(accept) => accept,
+            None => return true,
+        };
+
+        let accept = match accept.to_str() {
+            Ok(accept) => accept,
+            Err(_) => return false,
+        };
+
+        let (main_type, sub_type) = split_media_type(&self.media_type);
+
+        accept.split(',').any(|range| {
+            let mut params = range.split(';');
+            let (range_main, range_sub) = split_media_type(params.next().unwrap_or("").trim());
+
+            // Media range with zero quality is explicitly not acceptable
+            let rejected = params.any(|param| {
+                let param = param.trim();
+                param.starts_with("q=")
+                    && param[2..]
+                        .parse::<f32>()
+                        .is_ok_and(|quality| quality == 0.0)
+            });
+
+            let main_matched = range_main == "*" || range_main.eq_ignore_ascii_case(main_type);
+            let sub_matched = range_sub == "*" || range_sub.eq_ignore_ascii_case(sub_type);
+
+            !rejected && main_matched && sub_matched
+        })
+    }
+
+    fn rejection(&self) -> StatusCode {
+        StatusCode::NOT_ACCEPTABLE
+    }
+}
+
+pub struct Header {
+    name: HeaderName,
+    value: HeaderValue,
+}
+
+impl Guard for Header {
+    fn check(&self, req: &Parts) -> bool {
+        req.headers
+            .get_all(&self.name)
+            .iter()
+            .any(|value| value == self.value)
+    }
+}
+
+pub struct Query {
+    key: String,
+}
+
+impl Guard for Query {
+    fn check(&self, req: &Parts) -> bool {
+        let query = req.uri.query().unwrap_or_default();
+
+        form_urlencoded::parse(query.as_bytes()).any(|(key, _)| key == self.key.as_str())
+    }
+}
+
+/// Media type without the parameters
+fn media_type_essence(media_type: &str) -> &str {
+    media_type.split(';').next().unwrap_or("").trim()
+}
+
+fn split_media_type(media_type: &str) -> (&str, &str) {
+    let mut split = media_type.splitn(2, '/');
+
+    (
+        split.next().unwrap_or("").trim(),
+        split.next().unwrap_or("").trim(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, Request};
+
+    fn parts(uri: &str, headers: &[(&str, &str)]) -> Parts {
+        let mut req_builder = Request::builder();
+        req_builder.uri(uri);
+
+        for (name, value) in headers.iter() {
+            req_builder.header(*name, *value);
+        }
+
+        req_builder.body(Body::empty()).unwrap().into_parts().0
+    }
+
+    #[test]
+    fn guard_content_type_test() {
+        let guard = content_type("application/json");
+
+        assert!(guard.check(&parts("/", &[("content-type", "application/json")])));
+        assert!(guard.check(&parts(
+            "/",
+            &[("content-type", "Application/JSON; charset=utf-8")]
+        )));
+        assert!(!guard.check(&parts("/", &[("content-type", "text/html")])));
+        assert!(!guard.check(&parts("/", &[])));
+        assert_eq!(guard.rejection(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[test]
+    fn guard_accept_test() {
+        let guard = accept("text/html");
+
+        assert!(guard.check(&parts("/", &[])));
+        assert!(guard.check(&parts(
+            "/",
+            &[("accept", "text/html,application/xhtml+xml;q=0.9")]
+        )));
+        assert!(guard.check(&parts("/", &[("accept", "text/*")])));
+        assert!(guard.check(&parts("/", &[("accept", "*/*;q=0.8")])));
+        assert!(!guard.check(&parts("/", &[("accept", "application/json")])));
+        assert!(!guard.check(&parts(
+            "/",
+            &[("accept", "text/html;q=0, application/json")]
+        )));
+        assert_eq!(guard.rejection(), StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[test]
+    fn guard_header_and_query_test() {
+        let header_guard = header("x-api-key", "secret");
+        let query_guard = query("debug");
+
+        assert!(header_guard.check(&parts("/", &[("x-api-key", "secret")])));
+        assert!(!header_guard.check(&parts("/", &[("x-api-key", "other")])));
+        assert!(query_guard.check(&parts("/?page=1&debug", &[])));
+        assert!(!query_guard.check(&parts("/?page=1", &[])));
+        assert_eq!(query_guard.rejection(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn guard_closure_test() {
+        let guard = |req: &Parts| req.uri.path().starts_with("/admin");
+
+        assert!(guard.check(&parts("/admin/users", &[])));
+        assert!(!guard.check(&parts("/users", &[])));
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+/// HostPattern matches the request host label by label
+///
+/// `:name` captures a single label as the param `name` and `*` matches any single label.
+/// Matching is case insensitive and the port of the host is ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostPattern {
+    labels: Vec<String>,
+}
+
+impl HostPattern {
+    /// Panic if the pattern is empty or contains empty labels
+    pub fn new(pattern: &str) -> Self {
+        let labels = strip_port(pattern)
+            .split('.')
+            .map(|label| {
+                if label.is_empty() {
+                    panic!("ERROR: Empty label in host pattern: {}", pattern);
+                }
+
+                if label.starts_with(':') {
+                    label.to_string()
+                } else {
+                    label.to_ascii_lowercase()
+                }
+            })
+            .collect();
+
+        HostPattern { labels }
+    }
+
+    /// Match the host and return the captured params
+    pub fn matches(&self, host: &str) -> Option<HashMap<String, String>> {
+        let host = strip_port(host).trim_end_matches('.');
+        let mut params = HashMap::default();
+        let mut labels = host.split('.');
+
+        for pattern in self.labels.iter() {
+            let label = labels.next()?;
+
+            if label.is_empty() {
+                return None;
+            }
+
+            if let Some(key) = pattern.strip_prefix(':') {
+                params.insert(key.to_string(), label.to_ascii_lowercase());
+            } else if pattern != "*" && !pattern.eq_ignore_ascii_case(label) {
+                return None;
+            }
+        }
+
+        if labels.next().is_some() {
+            return None;
+        }
+
+        Some(params)
+    }
+}
+
+/// Drop the port from the host, IPv6 literal is kept with the brackets
+fn strip_port(host: &str) -> &str {
+    if host.starts_with('[') {
+        return match host.find(']') {
+            Some(end) => &host[..=end],
+            None => host,
+        };
+    }
+
+    match host.rfind(':') {
+        Some(pos) if host[pos + 1..].chars().all(|c| c.is_ascii_digit()) => &host[..pos],
+        _ => host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_pattern_exact_test() {
+        let pattern = HostPattern::new("api.example.com");
+
+        assert!(pattern.matches("api.example.com").is_some());
+        assert!(pattern.matches("API.Example.com:8080").is_some());
+        assert!(pattern.matches("api.example.com.").is_some());
+        assert!(pattern.matches("www.example.com").is_none());
+        assert!(pattern.matches("v1.api.example.com").is_none());
+        assert!(pattern.matches("example.com").is_none());
+    }
+
+    #[test]
+    fn host_pattern_param_test() {
+        let pattern = HostPattern::new(":tenant.example.com");
+
+        let params = pattern.matches("Acme.example.com:3000").unwrap();
+
+        assert_eq!(params.get("tenant").unwrap(), "acme");
+        assert!(pattern.matches("example.com").is_none());
+        assert!(pattern.matches(".example.com").is_none());
+        assert!(pattern.matches("a.b.example.com").is_none());
+    }
+
+    #[test]
+    fn host_pattern_wildcard_test() {
+        let pattern = HostPattern::new("*.example.com");
+
+        assert!(pattern.matches("www.example.com").is_some());
+        assert!(pattern.matches("example.com").is_none());
+
+        let pattern = HostPattern::new("[::1]:3000");
+
+        assert!(pattern.matches("[::1]").is_some());
+        assert!(pattern.matches("[::1]:8080").is_some());
+    }
+
+    #[should_panic]
+    #[test]
+    fn host_pattern_empty_label_test() {
+        HostPattern::new("api..example.com");
+    }
+}
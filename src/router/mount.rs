@@ -0,0 +1,136 @@
+use futures::{future, Future};
+use hyper::service::Service;
+use url::percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
+
+use super::path::wildcard_segments;
+use crate::context::Context;
+use crate::{Body, Request, Response, StatusCode, Uri};
+
+type MountFn = dyn Fn(Request<Body>) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send>
+    + Send
+    + Sync;
+
+/// Mount forwards the requests to a foreign service with the mount path stripped
+///
+/// The request reaches the service after the middlewares of the path, its body is already read.
+/// The mount path is taken from the matched route pattern, so it includes the prefixes
+/// of the routers the mount is merged into.
+pub struct Mount {
+    service: Box<MountFn>,
+}
+
+impl Mount {
+    /// Forward the requests to the hyper service, each request is served by a clone of it
+    /// Error of the service is responded with 500 Internal Server Error
+    pub fn service<S>(service: S) -> Self
+    where
+        S: Service<ReqBody = Body, ResBody = Body> + Clone + Send + Sync + 'static,
+        S::Future: Send + 'static,
+    {
+        Mount::new(move |req| {
+            let response = service.clone().call(req);
+
+            Box::new(response.then(|res| match res {
+                Ok(res) => Ok(res),
+                Err(err) => Ok(internal_error(&err.into().to_string())),
+            }))
+        })
+    }
+
+    /// Forward the requests to the function
+    pub fn function<F, R>(f: F) -> Self
+    where
+        F: Fn(Request<Body>) -> R + Send + Sync + 'static,
+        R: Future<Item = Response<Body>, Error = hyper::Error> + Send + 'static,
+    {
+        Mount::new(move |req| Box::new(f(req)))
+    }
+
+    fn new(
+        service: impl Fn(Request<Body>) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Mount {
+            service: Box::new(service),
+        }
+    }
+}
+
+/// Uri with the segments matched by the mount pattern removed, the query is kept
+fn strip_prefix(pattern: &str, uri: &Uri) -> Option<Uri> {
+    let segments = wildcard_segments(pattern, uri.path()).ok()?;
+    let mut path = String::new();
+
+    for segment in segments.iter() {
+        path.push('/');
+        path.extend(utf8_percent_encode(segment, PATH_SEGMENT_ENCODE_SET));
+    }
+
+    if path.is_empty() || (!segments.is_empty() && uri.path().ends_with('/')) {
+        path.push('/');
+    }
+
+    if let Some(query) = uri.query() {
+        path.push('?');
+        path.push_str(query);
+    }
+
+    path.parse().ok()
+}
+
+impl Mount {
+    /// Forward the request with the mount path stripped
+    pub(crate) fn call(
+        &self,
+        ctx: Context,
+    ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        let pattern = ctx.route_pattern().unwrap_or("/").to_string();
+        let mut req = ctx.into_request();
+
+        match strip_prefix(&pattern, req.uri()) {
+            Some(uri) => *req.uri_mut() = uri,
+            None => {
+                let mut response = Response::new(Body::from("400 Bad Request"));
+                *response.status_mut() = StatusCode::BAD_REQUEST;
+
+                return Box::new(future::ok(response));
+            }
+        }
+
+        (self.service)(req)
+    }
+}
+
+fn internal_error(message: &str) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::INTERNAL_SERVER_ERROR)
+        .body(Body::from(message.to_string()))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mount_strip_prefix_test() {
+        let test_cases = [
+            ("/legacy/api", "/"),
+            ("/legacy/api/", "/"),
+            ("/legacy/api/users/1", "/users/1"),
+            ("/legacy/api/users/?page=2", "/users/?page=2"),
+            ("/legacy//api/a%2Fb", "/a%2Fb"),
+        ];
+
+        for case in test_cases.iter() {
+            let uri = case.0.parse::<Uri>().unwrap();
+
+            assert_eq!(
+                strip_prefix("/legacy/api/*", &uri).unwrap().to_string(),
+                case.1
+            );
+        }
+    }
+}
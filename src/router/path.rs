@@ -0,0 +1,109 @@
+use url::percent_encoding::percent_decode;
+
+use crate::ObsidianError;
+
+/// Normalize the raw request path into percent-decoded segments
+///
+/// Empty segments and `.` are dropped, `..` resolves to the parent segment.
+/// Decoding happens per segment, so an encoded `%2F` stays inside its segment.
+/// Error if the path escapes the root or does not decode into valid UTF-8.
+pub fn normalize_path(path: &str) -> Result<Vec<String>, ObsidianError> {
+    let mut segments: Vec<String> = Vec::new();
+
+    for raw in path.split('/').filter(|key| !key.is_empty()) {
+        let segment = percent_decode(raw.as_bytes()).decode_utf8().map_err(|_| {
+            ObsidianError::PathError(format!("Invalid UTF-8 sequence in path segment {}", raw))
+        })?;
+
+        match segment.as_ref() {
+            "." => {}
+            ".." => {
+                if segments.pop().is_none() {
+                    return Err(ObsidianError::PathError(format!(
+                        "Path {} traverses above the root",
+                        path
+                    )));
+                }
+            }
+            _ => segments.push(segment.into_owned()),
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Normalized segments of the path captured by the trailing `*` of the matched route pattern
+/// Empty if the pattern has no wildcard, error if the path is not normalizable
+pub(crate) fn wildcard_segments(pattern: &str, path: &str) -> Result<Vec<String>, ObsidianError> {
+    if !pattern.ends_with('*') {
+        return Ok(Vec::default());
+    }
+
+    let prefix_len = pattern
+        .split('/')
+        .filter(|key| !key.is_empty() && *key != "*")
+        .count();
+
+    Ok(normalize_path(path)?.into_iter().skip(prefix_len).collect())
+}
+
+/// Whether the raw segments of the path are already normalized
+/// Such path contains no percent-encoding and no `.` or `..` segment
+pub fn is_normalized_path(path: &str) -> bool {
+    !path.contains('%')
+        && path
+            .split('/')
+            .all(|segment| segment != "." && segment != "..")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_path_decode_test() {
+        let segments = normalize_path("/users/John%20Doe/%E3%83%86%E3%82%B9%E3%83%88").unwrap();
+
+        assert_eq!(segments, vec!["users", "John Doe", "テスト"]);
+    }
+
+    #[test]
+    fn normalize_path_encoded_slash_test() {
+        let segments = normalize_path("/files/a%2Fb/c").unwrap();
+
+        assert_eq!(segments, vec!["files", "a/b", "c"]);
+    }
+
+    #[test]
+    fn normalize_path_dot_segment_test() {
+        let segments = normalize_path("//a/./b//../c/").unwrap();
+
+        assert_eq!(segments, vec!["a", "c"]);
+
+        let segments = normalize_path("/a/%2e%2E/b").unwrap();
+
+        assert_eq!(segments, vec!["b"]);
+    }
+
+    #[test]
+    fn normalize_path_traversal_test() {
+        assert!(normalize_path("/../etc/passwd").is_err());
+        assert!(normalize_path("/a/../../etc/passwd").is_err());
+        assert!(normalize_path("/a/%2e%2e/%2e%2e/etc").is_err());
+    }
+
+    #[test]
+    fn normalize_path_invalid_utf8_test() {
+        assert!(normalize_path("/users/%FF%FE").is_err());
+    }
+
+    #[test]
+    fn is_normalized_path_test() {
+        assert!(is_normalized_path("/"));
+        assert!(is_normalized_path("/users//1/"));
+        assert!(is_normalized_path("/files/.hidden"));
+        assert!(!is_normalized_path("/users/John%20Doe"));
+        assert!(!is_normalized_path("/users/./1"));
+        assert!(!is_normalized_path("/users/.."));
+    }
+}
@@ -107,7 +107,7 @@ impl<'de> FormDeserializer<'de> {
     }
 }
 
-impl<'de, 'a> de::Deserializer<'de> for &'a mut FormDeserializer<'de> {
+impl<'de> de::Deserializer<'de> for &mut FormDeserializer<'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
@@ -419,19 +419,15 @@ impl de::Error for Error {
 
 impl Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str(std::error::Error::description(self))
-    }
-}
-
-impl std::error::Error for Error {
-    fn description(&self) -> &str {
         match *self {
-            Error::Message(ref msg) => msg,
-            Error::NoneError => "Input should not be None",
+            Error::Message(ref msg) => formatter.write_str(msg),
+            Error::NoneError => formatter.write_str("Input should not be None"),
         }
     }
 }
 
+impl std::error::Error for Error {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
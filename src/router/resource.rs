@@ -1,29 +1,126 @@
+use hyper::http::request::Parts;
 use hyper::Method;
 use std::collections::HashMap;
 
-use super::Route;
+use super::{Route, TrailingSlash};
+use crate::StatusCode;
 
 /// Resource acts as the intermidiate interface for interaction of routing data structure
 /// Resource is binding with the path and handling all of the request method for that path
-#[derive(Clone, Debug)]
+///
+/// A method may hold multiple guarded routes, they are tried in the order of registration.
+/// Routes of the method `*` accept the methods without any route of their own.
+#[derive(Clone, Debug, Default)]
 pub struct Resource {
-    route_map: HashMap<Method, Route>,
+    route_map: HashMap<Method, Vec<Route>>,
 }
 
-impl Default for Resource {
-    fn default() -> Self {
-        Resource {
-            route_map: HashMap::new(),
+impl Resource {
+    /// Append the route to the candidates of the method and return it for configuration
+    /// Error with the method if an unguarded route of the method is registered,
+    /// as it accepts every request and any later candidate is unreachable
+    pub fn add_route(&mut self, method: Method, route: Route) -> Result<&mut Route, Method> {
+        let routes = self.route_map.entry(method.clone()).or_default();
+
+        if routes.iter().any(|route| route.guards.is_empty()) {
+            return Err(method);
         }
+
+        routes.push(route);
+        Ok(routes.last_mut().unwrap())
+    }
+
+    /// First registered route of the method
+    pub fn get_route(&self, method: &Method) -> Option<&Route> {
+        self.get_routes(method).first()
+    }
+
+    /// Candidate routes of the method in the order of registration
+    /// Routes of any method are the candidates if the method has no route
+    pub fn get_routes(&self, method: &Method) -> &[Route] {
+        self.route_map
+            .get(method)
+            .or_else(|| self.route_map.get(&any_method()))
+            .map(|routes| routes.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Iterate over the routes of all methods
+    pub fn routes(&self) -> impl Iterator<Item = &Route> {
+        self.route_map.values().flatten()
+    }
+
+    pub fn routes_mut(&mut self) -> impl Iterator<Item = &mut Route> {
+        self.route_map.values_mut().flatten()
+    }
+
+    pub fn into_routes(self) -> impl Iterator<Item = Route> {
+        self.route_map.into_values().flatten()
     }
 }
 
-impl Resource {
-    pub fn add_route(&mut self, method: Method, route: Route) -> Option<Route> {
-        self.route_map.insert(method, route)
+/// Method of the routes accepting any method, listed as `*` in the route table
+pub(crate) fn any_method() -> Method {
+    Method::from_bytes(b"*").unwrap()
+}
+
+/// Resources of the matched path resolved with the trailing slash policy
+pub struct PathResources<'a> {
+    exact: &'a Resource,
+    other: &'a Resource,
+    policy: TrailingSlash,
+}
+
+impl<'a> PathResources<'a> {
+    /// Resources in the form matching and not matching the trailing slash of the request
+    pub fn new(exact: &'a Resource, other: &'a Resource, policy: TrailingSlash) -> Self {
+        PathResources {
+            exact,
+            other,
+            policy,
+        }
     }
 
-    pub fn get_route(&self, method: &Method) -> Option<&Route> {
-        self.route_map.get(&method)
+    /// Candidate routes of the method in the order they are tried
+    pub fn get_routes(&self, method: &Method) -> impl Iterator<Item = &'a Route> {
+        let fallback = match self.policy {
+            TrailingSlash::Ignore if self.exact.get_routes(method).is_empty() => {
+                self.other.get_routes(method)
+            }
+            _ => &[],
+        };
+
+        self.exact.get_routes(method).iter().chain(fallback.iter())
+    }
+
+    /// First candidate route accepted by all of its guards
+    /// Error with the rejection of the first failed guard of the first candidate,
+    /// or 404 Not Found if the method has no route
+    pub fn select_route(&self, req: &Parts) -> Result<&'a Route, StatusCode> {
+        let mut rejection = None;
+
+        for route in self.get_routes(&req.method) {
+            match route.guards.iter().find(|guard| !guard.check(req)) {
+                Some(guard) => {
+                    rejection.get_or_insert_with(|| guard.rejection());
+                }
+                None => return Ok(route),
+            }
+        }
+
+        Err(rejection.unwrap_or(StatusCode::NOT_FOUND))
+    }
+
+    /// Redirect status if the method is only registered with the other trailing slash form
+    pub fn get_redirect(&self, method: &Method) -> Option<StatusCode> {
+        match self.policy {
+            TrailingSlash::Redirect(status)
+                if self.exact.get_route(method).is_none()
+                    && self.other.get_route(method).is_some() =>
+            {
+                Some(status)
+            }
+            _ => None,
+        }
     }
 }
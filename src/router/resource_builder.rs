@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use super::{ResourceController, Router};
+
+/// ResourceBuilder registers the actions of a resource controller one by one
+///
+/// Collection actions are registered on the path without its trailing slash
+/// and member actions below it with `:id` as the member param.
+///
+/// | Action    | Method | Path        |
+/// |-----------|--------|-------------|
+/// | `index`   | GET    | `/path`     |
+/// | `create`  | POST   | `/path`     |
+/// | `show`    | GET    | `/path/:id` |
+/// | `update`  | PUT    | `/path/:id` |
+/// | `patch`   | PATCH  | `/path/:id` |
+/// | `destroy` | DELETE | `/path/:id` |
+pub struct ResourceBuilder<'a, C> {
+    router: &'a mut Router,
+    controller: Arc<C>,
+    path: String,
+    member_path: String,
+}
+
+impl<'a, C: ResourceController> ResourceBuilder<'a, C> {
+    pub(crate) fn new(router: &'a mut Router, path: &str, controller: C) -> Self {
+        let path = path.trim_end_matches('/');
+
+        ResourceBuilder {
+            router,
+            controller: Arc::new(controller),
+            path: if path.is_empty() { "/" } else { path }.to_string(),
+            member_path: format!("{}/:id", path),
+        }
+    }
+
+    pub fn index(self) -> Self {
+        let controller = self.controller.clone();
+        self.router
+            .get(&self.path, move |ctx| controller.index(ctx));
+        self
+    }
+
+    pub fn create(self) -> Self {
+        let controller = self.controller.clone();
+        self.router
+            .post(&self.path, move |ctx| controller.create(ctx));
+        self
+    }
+
+    pub fn show(self) -> Self {
+        let controller = self.controller.clone();
+        self.router
+            .get(&self.member_path, move |ctx| controller.show(ctx));
+        self
+    }
+
+    pub fn update(self) -> Self {
+        let controller = self.controller.clone();
+        self.router
+            .put(&self.member_path, move |ctx| controller.update(ctx));
+        self
+    }
+
+    pub fn patch(self) -> Self {
+        let controller = self.controller.clone();
+        self.router
+            .patch(&self.member_path, move |ctx| controller.patch(ctx));
+        self
+    }
+
+    pub fn destroy(self) -> Self {
+        let controller = self.controller.clone();
+        self.router
+            .delete(&self.member_path, move |ctx| controller.destroy(ctx));
+        self
+    }
+}
@@ -0,0 +1,59 @@
+use super::{Responder, ResponseResult};
+use crate::context::Context;
+use crate::StatusCode;
+
+/// ResourceController handles the conventional REST routes of a resource
+///
+/// `Router::resource` returns a `ResourceBuilder` which registers the actions one by one,
+/// so other methods on the paths stay free for custom routes.
+/// Actions registered without being implemented respond 501 Not Implemented.
+///
+/// # Example
+///
+/// ```
+/// # use obsidian::context::Context;
+/// # use obsidian::router::{ResourceController, Responder, ResponseResult, Router};
+/// struct UsersController;
+///
+/// impl ResourceController for UsersController {
+///     fn index(&self, _ctx: Context) -> ResponseResult {
+///         "users".respond_to()
+///     }
+///
+///     fn show(&self, ctx: Context) -> ResponseResult {
+///         let id: String = ctx.param("id").unwrap_or_default();
+///         format!("user {}", id).respond_to()
+///     }
+/// }
+///
+/// let mut router = Router::new();
+///
+/// router.resource("/users", UsersController).index().show();
+///
+/// assert!(router.search_route("/users/1").is_some());
+/// ```
+pub trait ResourceController: Send + Sync + 'static {
+    fn index(&self, _ctx: Context) -> ResponseResult {
+        StatusCode::NOT_IMPLEMENTED.respond_to()
+    }
+
+    fn show(&self, _ctx: Context) -> ResponseResult {
+        StatusCode::NOT_IMPLEMENTED.respond_to()
+    }
+
+    fn create(&self, _ctx: Context) -> ResponseResult {
+        StatusCode::NOT_IMPLEMENTED.respond_to()
+    }
+
+    fn update(&self, _ctx: Context) -> ResponseResult {
+        StatusCode::NOT_IMPLEMENTED.respond_to()
+    }
+
+    fn patch(&self, _ctx: Context) -> ResponseResult {
+        StatusCode::NOT_IMPLEMENTED.respond_to()
+    }
+
+    fn destroy(&self, _ctx: Context) -> ResponseResult {
+        StatusCode::NOT_IMPLEMENTED.respond_to()
+    }
+}
@@ -19,7 +19,7 @@ pub fn body(body: impl ResponseBody) -> ResponseResult {
 pub fn json(body: impl Serialize, status_code: StatusCode) -> ResponseResult {
     let serialized_obj = match serde_json::to_string(&body) {
         Ok(val) => val,
-        Err(e) => std::error::Error::description(&e).to_string(),
+        Err(e) => e.to_string(),
     };
 
     let body = serialized_obj.into_body();
@@ -49,6 +49,9 @@ pub fn file(file_path: &str) -> ResponseResult {
                 })
         })
         .or_else(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(error = %err, "static file not found");
+            #[cfg(not(feature = "tracing"))]
             dbg!(&err);
             Ok(Response::builder()
                 .status(StatusCode::NOT_FOUND)
@@ -1,16 +1,39 @@
 use std::sync::Arc;
 
-use super::Handler;
+use super::{Guard, Handler};
+use crate::app::Endpoint;
+use crate::middleware::Middleware;
 use crate::Method;
 
 pub struct Route {
     pub method: Method,
-    pub handler: Arc<dyn Handler>,
+    pub endpoint: Endpoint,
+    pub path: String,
+    pub name: Option<String>,
+    pub middlewares: Vec<Arc<dyn Middleware>>,
+    pub guards: Vec<Arc<dyn Guard>>,
+    /// Set by `Router::freeze` so that the requests only clone the chain
+    pub(crate) chain: Option<RouteChain>,
+}
+
+/// Middlewares of the path followed by the route middlewares, with the pattern of the route
+#[derive(Clone)]
+pub(crate) struct RouteChain {
+    pub middlewares: Arc<[Arc<dyn Middleware>]>,
+    pub pattern: Arc<str>,
 }
 
 impl std::fmt::Debug for Route {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Route {{ method: {} }}", self.method)
+        write!(
+            f,
+            "Route {{ method: {}, path: {}, name: {:?}, middlewares: {}, guards: {} }}",
+            self.method,
+            self.path,
+            self.name,
+            self.middlewares.len(),
+            self.guards.len()
+        )
     }
 }
 
@@ -18,16 +41,31 @@ impl Clone for Route {
     fn clone(&self) -> Route {
         Route {
             method: self.method.clone(),
-            handler: self.handler.clone(),
+            endpoint: self.endpoint.clone(),
+            path: self.path.clone(),
+            name: self.name.clone(),
+            middlewares: self.middlewares.clone(),
+            guards: self.guards.clone(),
+            chain: self.chain.clone(),
         }
     }
 }
 
 impl Route {
     pub fn new(method: Method, handler: impl Handler) -> Self {
+        Route::with_endpoint(method, Endpoint::Handler(Arc::new(handler)))
+    }
+
+    /// Route sharing the endpoint with other routes
+    pub fn with_endpoint(method: Method, endpoint: Endpoint) -> Self {
         Route {
             method,
-            handler: Arc::new(handler),
+            endpoint,
+            path: String::from("/"),
+            name: None,
+            middlewares: Vec::default(),
+            guards: Vec::default(),
+            chain: None,
         }
     }
 }
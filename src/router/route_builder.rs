@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use super::{Guard, Route};
+use crate::middleware::Middleware;
+
+/// RouteBuilder configures the route returned by the registration methods
+///
+/// # Example
+///
+/// ```
+/// # use obsidian::App;
+/// # use obsidian::middleware::Logger;
+/// # use obsidian::router::guard;
+/// let mut app = App::new();
+///
+/// app.get("/users/:id", |_ctx| "user").name("user.show");
+/// app.post("/users", |_ctx| "created").with(Logger::new());
+/// app.post("/upload", |_ctx| "json").guard(guard::content_type("application/json"));
+/// ```
+pub struct RouteBuilder<'a> {
+    route: &'a mut Route,
+}
+
+impl<'a> RouteBuilder<'a> {
+    pub fn new(route: &'a mut Route) -> Self {
+        RouteBuilder { route }
+    }
+
+    /// Name the route for reverse URL generation through `url_for`
+    pub fn name(self, name: &str) -> Self {
+        self.route.name = Some(name.to_string());
+        self
+    }
+
+    /// Apply middleware to this route only
+    /// Other methods on the same path and the routes below it are not affected
+    pub fn with(self, middleware: impl Middleware) -> Self {
+        self.route.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    /// Match the route only if the guard accepts the request
+    /// Otherwise the next route registered for the same path and method is tried
+    ///
+    /// Guarded routes must be registered before the unguarded fallback of the method
+    pub fn guard(self, guard: impl Guard) -> Self {
+        self.route.guards.push(Arc::new(guard));
+        self
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use hyper::http::request::Parts;
+use hyper::Method;
+
+use super::route_trie::RouteValue;
+use super::{is_normalized_path, normalize_path, PathResources, Route, TrailingSlash};
+use crate::middleware::Middleware;
+use crate::StatusCode;
+
+/// RouteMatcher is the read-only form of the router compiled by `Router::freeze`
+///
+/// Static routes are resolved through a hash lookup and the others through a segment tree.
+/// Middleware chains are computed once per route, so a lookup of a path without
+/// percent-encoding or dot segments does not allocate.
+pub struct RouteMatcher {
+    static_routes: HashMap<String, usize>,
+    nodes: Vec<MatchNode>,
+    leaves: Vec<Leaf>,
+    policy: TrailingSlash,
+}
+
+#[derive(Default)]
+struct MatchNode {
+    statics: HashMap<String, usize>,
+    param: Option<usize>,
+    wildcard: Option<usize>,
+    leaf: Option<usize>,
+}
+
+struct Leaf {
+    value: RouteValue,
+    /// Param names with the position of their segment
+    params: Vec<(String, usize)>,
+    is_wildcard: bool,
+}
+
+impl RouteMatcher {
+    /// Compile the route values paired with their route pattern
+    pub fn new(values: Vec<(String, RouteValue)>, policy: TrailingSlash) -> Self {
+        let mut matcher = RouteMatcher {
+            static_routes: HashMap::default(),
+            nodes: vec![MatchNode::default()],
+            leaves: Vec::with_capacity(values.len()),
+            policy,
+        };
+
+        for (pattern, value) in values {
+            matcher.insert(&pattern, value);
+        }
+
+        matcher
+    }
+
+    fn insert(&mut self, pattern: &str, mut value: RouteValue) {
+        value.freeze_routes();
+
+        let segments = pattern
+            .split('/')
+            .filter(|key| !key.is_empty())
+            .collect::<Vec<&str>>();
+        let leaf_index = self.leaves.len();
+        let mut params = vec![];
+        let mut curr = 0;
+
+        for (pos, segment) in segments.iter().enumerate() {
+            if *segment == "*" {
+                self.nodes[curr].wildcard.get_or_insert(leaf_index);
+                self.leaves.push(Leaf {
+                    value,
+                    params,
+                    is_wildcard: true,
+                });
+                return;
+            }
+
+            let next = match segment.strip_prefix(':') {
+                Some(name) => {
+                    params.push((name.to_string(), pos));
+                    self.nodes[curr].param
+                }
+                None => self.nodes[curr].statics.get(*segment).copied(),
+            };
+
+            curr = match next {
+                Some(next) => next,
+                None => {
+                    let next = self.nodes.len();
+                    self.nodes.push(MatchNode::default());
+
+                    if segment.starts_with(':') {
+                        self.nodes[curr].param = Some(next);
+                    } else {
+                        self.nodes[curr].statics.insert(segment.to_string(), next);
+                    }
+
+                    next
+                }
+            };
+        }
+
+        self.nodes[curr].leaf.get_or_insert(leaf_index);
+
+        if params.is_empty() {
+            self.static_routes
+                .entry(segments.join("/"))
+                .or_insert(leaf_index);
+        }
+
+        self.leaves.push(Leaf {
+            value,
+            params,
+            is_wildcard: false,
+        });
+    }
+
+    /// Match the request path, statics take precedence over params and params over wildcards
+    pub fn search_route<'a>(&'a self, path: &'a str) -> Option<RouteMatch<'a>> {
+        let segments = if is_normalized_path(path) {
+            Segments::Raw(path)
+        } else {
+            Segments::Decoded(normalize_path(path).ok()?)
+        };
+
+        let static_leaf = match segments {
+            Segments::Raw(path) => {
+                let key = path.trim_matches('/');
+
+                if key.contains("//") {
+                    None
+                } else {
+                    self.static_routes.get(key).copied()
+                }
+            }
+            Segments::Decoded(_) => None,
+        };
+
+        let leaf = match static_leaf {
+            Some(leaf) => leaf,
+            None => self.find(0, segments.iter(), true)?,
+        };
+        let leaf = &self.leaves[leaf];
+
+        // Wildcard consumes the rest of the path including the trailing slash
+        let trailing_slash =
+            segments.iter().next().is_some() && !leaf.is_wildcard && path.ends_with('/');
+
+        Some(RouteMatch {
+            leaf,
+            segments,
+            trailing_slash,
+            policy: self.policy,
+        })
+    }
+
+    fn find<'s, I>(&self, node: usize, mut segments: I, is_root: bool) -> Option<usize>
+    where
+        I: Iterator<Item = &'s str> + Clone,
+    {
+        let node = &self.nodes[node];
+
+        let segment = match segments.next() {
+            Some(segment) => segment,
+            // Wildcard also matches the path without the remaining segments except the root
+            None if is_root => return node.leaf,
+            None => return node.leaf.or(node.wildcard),
+        };
+
+        if let Some(next) = node.statics.get(segment) {
+            if let Some(leaf) = self.find(*next, segments.clone(), false) {
+                return Some(leaf);
+            }
+        }
+
+        if let Some(next) = node.param {
+            if let Some(leaf) = self.find(next, segments, false) {
+                return Some(leaf);
+            }
+        }
+
+        node.wildcard
+    }
+}
+
+/// Path segments borrowed from the raw path or decoded when the path is not normalized
+enum Segments<'a> {
+    Raw(&'a str),
+    Decoded(Vec<String>),
+}
+
+impl<'a> Segments<'a> {
+    fn iter(&self) -> impl Iterator<Item = &str> + Clone {
+        let (raw, decoded) = match self {
+            Segments::Raw(path) => (Some(path.split('/').filter(is_not_empty)), None),
+            Segments::Decoded(segments) => (None, Some(segments.iter().map(String::as_str))),
+        };
+
+        raw.into_iter()
+            .flatten()
+            .chain(decoded.into_iter().flatten())
+    }
+}
+
+fn is_not_empty(segment: &&str) -> bool {
+    !segment.is_empty()
+}
+
+/// Result of `RouteMatcher::search_route` borrowing the compiled routes
+pub struct RouteMatch<'a> {
+    leaf: &'a Leaf,
+    segments: Segments<'a>,
+    trailing_slash: bool,
+    policy: TrailingSlash,
+}
+
+impl<'a> RouteMatch<'a> {
+    pub fn get_route(&self, method: &Method) -> Option<&'a Route> {
+        self.get_resources().get_routes(method).next()
+    }
+
+    /// Candidate routes of the method in the order they are tried
+    pub fn get_routes(&self, method: &Method) -> impl Iterator<Item = &'a Route> {
+        self.get_resources().get_routes(method)
+    }
+
+    /// First candidate route accepted by all of its guards
+    pub fn select_route(&self, req: &Parts) -> Result<&'a Route, StatusCode> {
+        self.get_resources().select_route(req)
+    }
+
+    /// Redirect status if the method is only registered with the other trailing slash form
+    pub fn get_redirect(&self, method: &Method) -> Option<StatusCode> {
+        self.get_resources().get_redirect(method)
+    }
+
+    fn get_resources(&self) -> PathResources<'a> {
+        self.leaf
+            .value
+            .get_resources(self.trailing_slash, self.policy)
+    }
+
+    /// Middlewares of the path in the order they are applied
+    pub fn get_middlewares(&self) -> &'a [Arc<dyn Middleware>] {
+        self.leaf.value.get_middlewares()
+    }
+
+    /// Iterate over the params without allocation
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        let mut names = self.leaf.params.iter().peekable();
+
+        self.segments
+            .iter()
+            .enumerate()
+            .filter_map(move |(pos, segment)| match names.peek() {
+                Some((name, param_pos)) if *param_pos == pos => {
+                    names.next();
+                    Some((name.as_str(), segment))
+                }
+                _ => None,
+            })
+    }
+
+    pub fn get_params(&self) -> HashMap<String, String> {
+        self.params()
+            .map(|(key, val)| (key.to_string(), val.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Context;
+    use crate::router::{Responder, Router};
+
+    fn handler(_ctx: Context) -> impl Responder {
+        "handler"
+    }
+
+    fn router() -> Router {
+        let mut router = Router::new();
+
+        router.get("/", handler);
+        router.get("/users", handler);
+        router.get("/users/:id", handler);
+        router.get("/users/:id/posts/:post_id/", handler);
+        router.get("/test/radix", handler);
+        router.get("/test/radix2", handler);
+        router.get("/files/*", handler);
+        router.get("/files/readme", handler);
+        router.get("/encoded/a b", handler);
+
+        router
+    }
+
+    #[test]
+    fn route_matcher_parity_test() {
+        let router = router();
+        let matcher = router.freeze();
+
+        let test_cases = [
+            "/",
+            "/users",
+            "/users/",
+            "//users//1",
+            "/users/1",
+            "/users/John%20Doe",
+            "/users/1/posts/2",
+            "/users/1/posts/2/",
+            "/users/1/posts",
+            "/test/radix",
+            "/test/radix2",
+            "/test/radix3",
+            "/files",
+            "/files/docs/readme.md",
+            "/files/readme",
+            "/encoded/a%20b",
+            "/users/./1",
+            "/unknown",
+        ];
+
+        for path in test_cases.iter() {
+            let expected = router.search_route(path);
+            let actual = matcher.search_route(path);
+
+            assert_eq!(expected.is_some(), actual.is_some(), "path {}", path);
+
+            if let (Some(expected), Some(actual)) = (expected, actual) {
+                assert_eq!(
+                    expected.get_route(&Method::GET).map(|route| &route.path),
+                    actual.get_route(&Method::GET).map(|route| &route.path),
+                    "path {}",
+                    path
+                );
+                assert_eq!(expected.get_params(), actual.get_params(), "path {}", path);
+            }
+        }
+    }
+
+    #[test]
+    fn route_matcher_params_test() {
+        let matcher = router().freeze();
+
+        let result = matcher.search_route("/users/7/posts/%E3%83%86/").unwrap();
+        let params = result.params().collect::<Vec<(&str, &str)>>();
+
+        assert_eq!(params, vec![("id", "7"), ("post_id", "テ")]);
+    }
+
+    #[test]
+    fn route_matcher_middleware_test() {
+        let mut router = router();
+
+        router.use_service(crate::middleware::Logger::new());
+        router.use_service_to("/users", crate::middleware::Logger::new());
+
+        let matcher = router.freeze();
+
+        assert_eq!(
+            matcher.search_route("/").unwrap().get_middlewares().len(),
+            1
+        );
+        assert_eq!(
+            matcher
+                .search_route("/users/1")
+                .unwrap()
+                .get_middlewares()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn route_matcher_route_chain_test() {
+        let mut router = router();
+
+        router.use_service_to("/users", crate::middleware::Logger::new());
+        router
+            .get("/users/:id/posts", handler)
+            .with(crate::middleware::Logger::new());
+
+        let matcher = router.freeze();
+        let result = matcher.search_route("/users/1/posts").unwrap();
+        let chain = result
+            .get_route(&Method::GET)
+            .and_then(|route| route.chain.as_ref())
+            .unwrap();
+
+        assert_eq!(chain.middlewares.len(), 2);
+        assert_eq!(&*chain.pattern, "/users/:id/posts");
+    }
+
+    #[test]
+    fn route_matcher_trailing_slash_test() {
+        let mut router = Router::new();
+
+        router.trailing_slash(TrailingSlash::Redirect(StatusCode::MOVED_PERMANENTLY));
+        router.get("/users/", handler);
+
+        let matcher = router.freeze();
+
+        assert_eq!(
+            matcher
+                .search_route("/users")
+                .unwrap()
+                .get_redirect(&Method::GET),
+            Some(StatusCode::MOVED_PERMANENTLY)
+        );
+        assert!(matcher
+            .search_route("/users/")
+            .unwrap()
+            .get_route(&Method::GET)
+            .is_some());
+    }
+}
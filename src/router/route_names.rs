@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use url::percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
+
+use crate::ObsidianError;
+
+/// RouteNames maps the route names to their full path pattern for reverse URL generation
+#[derive(Clone, Debug, Default)]
+pub struct RouteNames {
+    patterns: HashMap<String, String>,
+}
+
+impl RouteNames {
+    /// Panic if the name is already registered
+    pub fn insert(&mut self, name: &str, pattern: &str) {
+        if let Some(duplicated) = self.patterns.insert(name.to_string(), pattern.to_string()) {
+            panic!(
+                "Duplicated route name '{}' at '{}' and '{}' detected",
+                name, duplicated, pattern
+            );
+        }
+    }
+
+    pub fn get_pattern(&self, name: &str) -> Option<&str> {
+        self.patterns.get(name).map(|pattern| pattern.as_str())
+    }
+
+    /// Build the path of the named route
+    /// Params are percent-encoded, `*` is used as the key of the wildcard
+    ///
+    /// Error if the name is not registered or a param is missing
+    pub fn url_for<I, K, V>(&self, name: &str, params: I) -> Result<String, ObsidianError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Display,
+    {
+        let pattern = self.get_pattern(name).ok_or_else(|| {
+            ObsidianError::GeneralError(format!("Route name {} is not registered", name))
+        })?;
+
+        let params = params
+            .into_iter()
+            .map(|(key, val)| (key.as_ref().to_string(), val.to_string()))
+            .collect::<HashMap<String, String>>();
+
+        fill_pattern(pattern, &params).map_err(|key| {
+            ObsidianError::ParamError(format!("Missing param {} for route name {}", key, name))
+        })
+    }
+}
+
+/// Build the path of the pattern with the percent-encoded params
+/// `*` is used as the key of the wildcard, error with the key of the missing param
+pub(crate) fn fill_pattern(
+    pattern: &str,
+    params: &HashMap<String, String>,
+) -> Result<String, String> {
+    let mut url = String::new();
+
+    for segment in pattern.split('/').filter(|key| !key.is_empty()) {
+        url.push('/');
+
+        if segment.starts_with(':') || segment == "*" {
+            let key = segment.trim_start_matches(':');
+            let val = params.get(key).ok_or_else(|| key.to_string())?;
+
+            if segment == "*" {
+                // Wildcard keeps the separators of the remaining path
+                let rest = val
+                    .split('/')
+                    .map(|x| utf8_percent_encode(x, PATH_SEGMENT_ENCODE_SET).to_string())
+                    .collect::<Vec<String>>();
+
+                url.push_str(rest.join("/").trim_start_matches('/'));
+            } else {
+                url.extend(utf8_percent_encode(val, PATH_SEGMENT_ENCODE_SET));
+            }
+        } else {
+            url.push_str(segment);
+        }
+    }
+
+    if url.is_empty() || pattern.ends_with('/') {
+        url.push('/');
+    }
+
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_names_url_for_test() {
+        let mut route_names = RouteNames::default();
+
+        route_names.insert("index", "/");
+        route_names.insert("user.show", "/users/:id");
+        route_names.insert("user.posts", "/users/:id/posts/");
+        route_names.insert("files", "/files/*");
+
+        let empty: Vec<(&str, &str)> = vec![];
+
+        assert_eq!(route_names.url_for("index", empty).unwrap(), "/");
+        assert_eq!(
+            route_names.url_for("user.show", vec![("id", 1)]).unwrap(),
+            "/users/1"
+        );
+        assert_eq!(
+            route_names
+                .url_for("user.show", vec![("id", "John Doe/テスト")])
+                .unwrap(),
+            "/users/John%20Doe%2F%E3%83%86%E3%82%B9%E3%83%88"
+        );
+        assert_eq!(
+            route_names.url_for("user.posts", vec![("id", 1)]).unwrap(),
+            "/users/1/posts/"
+        );
+        assert_eq!(
+            route_names
+                .url_for("files", vec![("*", "docs/read me.txt")])
+                .unwrap(),
+            "/files/docs/read%20me.txt"
+        );
+    }
+
+    #[test]
+    fn route_names_url_for_error_test() {
+        let mut route_names = RouteNames::default();
+
+        route_names.insert("user.show", "/users/:id");
+
+        assert!(route_names.url_for("user.show", vec![("name", 1)]).is_err());
+        assert!(route_names.url_for("user.edit", vec![("id", 1)]).is_err());
+    }
+
+    #[should_panic]
+    #[test]
+    fn route_names_duplicate_test() {
+        let mut route_names = RouteNames::default();
+
+        route_names.insert("user.show", "/users/:id");
+        route_names.insert("user.show", "/members/:id");
+    }
+}
@@ -0,0 +1,58 @@
+use super::Router;
+
+/// RouteService registers its routes into the router
+///
+/// It is implemented by the functions annotated with the route attributes,
+/// `routes!` groups several services into one.
+///
+/// # Example
+///
+/// ```
+/// # use obsidian::context::Context;
+/// # use obsidian::router::{Responder, Router};
+/// # use obsidian::{get, post, routes};
+/// #[get("/users/:id", name = "user.show")]
+/// fn show_user(_ctx: Context) -> impl Responder {
+///     "user"
+/// }
+///
+/// #[post("/users")]
+/// fn create_user(_ctx: Context) -> impl Responder {
+///     "created"
+/// }
+///
+/// let mut router = Router::new();
+///
+/// router.service(routes![show_user, create_user]);
+///
+/// assert_eq!(router.url_for("user.show", vec![("id", 1)]).unwrap(), "/users/1");
+/// ```
+///
+/// Invalid paths are rejected at compile time
+///
+/// ```compile_fail
+/// # use obsidian::context::Context;
+/// #[obsidian::get("/files/*/raw")]
+/// fn raw_file(_ctx: Context) -> &'static str {
+///     "raw"
+/// }
+/// ```
+pub trait RouteService {
+    fn register(&self, router: &mut Router);
+}
+
+impl RouteService for Vec<Box<dyn RouteService>> {
+    fn register(&self, router: &mut Router) {
+        for service in self.iter() {
+            service.register(router);
+        }
+    }
+}
+
+/// Group the route services to be registered together
+#[macro_export]
+macro_rules! routes {
+    ($($service:path),* $(,)?) => {
+        vec![$(Box::new($service) as Box<dyn $crate::router::RouteService>),*]
+    };
+}
@@ -0,0 +1,139 @@
+use std::fmt;
+
+use crate::Method;
+
+/// RouteInfo describes a registered route and the middlewares applied to it
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteInfo {
+    pub method: Method,
+    pub path: String,
+    pub name: Option<String>,
+    pub middlewares: Vec<&'static str>,
+}
+
+/// RouteTable lists the registered routes sorted by path and method
+///
+/// Display implementation prints the table for startup logs
+#[derive(Clone, Debug, Default)]
+pub struct RouteTable {
+    routes: Vec<RouteInfo>,
+}
+
+impl RouteTable {
+    pub fn new(mut routes: Vec<RouteInfo>) -> Self {
+        routes.sort_by(|a, b| {
+            a.path
+                .cmp(&b.path)
+                .then_with(|| a.method.as_str().cmp(b.method.as_str()))
+        });
+
+        RouteTable { routes }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, RouteInfo> {
+        self.routes.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+}
+
+impl IntoIterator for RouteTable {
+    type Item = RouteInfo;
+    type IntoIter = std::vec::IntoIter<RouteInfo>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.routes.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a RouteTable {
+    type Item = &'a RouteInfo;
+    type IntoIter = std::slice::Iter<'a, RouteInfo>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.routes.iter()
+    }
+}
+
+impl fmt::Display for RouteTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let method_width = self
+            .routes
+            .iter()
+            .map(|route| route.method.as_str().len())
+            .max()
+            .unwrap_or(0);
+        let path_width = self
+            .routes
+            .iter()
+            .map(|route| route.path.chars().count())
+            .max()
+            .unwrap_or(0);
+        let name_width = self
+            .routes
+            .iter()
+            .map(|route| route.name.as_ref().map_or(1, |name| name.chars().count()))
+            .max()
+            .unwrap_or(0);
+
+        for route in self.routes.iter() {
+            writeln!(
+                f,
+                "{:method_width$}  {:path_width$}  {:name_width$}  [{}]",
+                route.method.as_str(),
+                route.path,
+                route.name.as_deref().unwrap_or("-"),
+                route.middlewares.join(", "),
+                method_width = method_width,
+                path_width = path_width,
+                name_width = name_width,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_table_display_test() {
+        let table = RouteTable::new(vec![
+            RouteInfo {
+                method: Method::POST,
+                path: "/users".to_string(),
+                name: None,
+                middlewares: vec![],
+            },
+            RouteInfo {
+                method: Method::GET,
+                path: "/users/:id".to_string(),
+                name: Some("user.show".to_string()),
+                middlewares: vec!["Logger", "Auth"],
+            },
+            RouteInfo {
+                method: Method::GET,
+                path: "/users".to_string(),
+                name: Some("user.index".to_string()),
+                middlewares: vec!["Logger"],
+            },
+        ]);
+
+        let expected = "\
+GET   /users      user.index  [Logger]
+POST  /users      -           []
+GET   /users/:id  user.show   [Logger, Auth]
+";
+
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.to_string(), expected);
+    }
+}
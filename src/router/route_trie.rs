@@ -2,17 +2,23 @@ use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 
+use hyper::http::request::Parts;
 use hyper::Method;
 
 use crate::middleware::Middleware;
-use crate::router::Resource;
-use crate::router::Route;
-use crate::ObsidianError;
+use crate::router::normalize_path;
+use crate::router::RouteNames;
+use crate::router::TrailingSlash;
+use crate::router::{PathResources, Resource};
+use crate::router::{Route, RouteChain};
+use crate::router::{RouteInfo, RouteTable};
+use crate::{ObsidianError, StatusCode};
 
 #[derive(Clone, Default)]
 pub struct RouteValue {
     middlewares: Vec<Arc<dyn Middleware>>,
     route: Resource,
+    slash_route: Resource,
 }
 
 impl fmt::Debug for RouteValue {
@@ -22,26 +28,144 @@ impl fmt::Debug for RouteValue {
 }
 
 impl RouteValue {
-    pub fn new(middlewares: Vec<Arc<dyn Middleware>>, route: Resource) -> Self {
-        RouteValue { middlewares, route }
+    pub fn new(
+        middlewares: Vec<Arc<dyn Middleware>>,
+        route: Resource,
+        slash_route: Resource,
+    ) -> Self {
+        RouteValue {
+            middlewares,
+            route,
+            slash_route,
+        }
+    }
+
+    /// Combine the routes method by method and append the middlewares of the other value
+    /// Panic if the same method is registered in both values
+    fn merge(&mut self, other: RouteValue) {
+        self.middlewares.extend(other.middlewares);
+
+        for (trailing_slash, resource) in [(false, other.route), (true, other.slash_route)] {
+            for route in resource.into_routes() {
+                self.add_route(trailing_slash, route);
+            }
+        }
+    }
+
+    /// Group the routes by the trailing slash of their pattern, or all into `route` if not split
+    /// Panic if the same method is registered for both forms of the path when grouped together
+    fn regroup(&mut self, split: bool) {
+        let routes = std::mem::take(&mut self.route)
+            .into_routes()
+            .chain(std::mem::take(&mut self.slash_route).into_routes())
+            .collect::<Vec<Route>>();
+
+        for route in routes {
+            let trailing_slash = split && route.path.len() > 1 && route.path.ends_with('/');
+
+            self.add_route(trailing_slash, route);
+        }
+    }
+
+    /// Panic if an unguarded route of the method is already registered for the form
+    fn add_route(&mut self, trailing_slash: bool, route: Route) -> &mut Route {
+        let path = route.path.clone();
+
+        match self
+            .resource_mut(trailing_slash)
+            .add_route(route.method.clone(), route)
+        {
+            Ok(route) => route,
+            Err(duplicated) => panic!(
+                "Duplicated route method '{}' at '{}' detected",
+                duplicated, path
+            ),
+        }
+    }
+
+    /// Resources resolved for the trailing slash form of the request
+    pub fn get_resources(&self, trailing_slash: bool, policy: TrailingSlash) -> PathResources<'_> {
+        if trailing_slash {
+            PathResources::new(&self.slash_route, &self.route, policy)
+        } else {
+            PathResources::new(&self.route, &self.slash_route, policy)
+        }
+    }
+
+    pub fn get_middlewares(&self) -> &[Arc<dyn Middleware>] {
+        &self.middlewares
+    }
+
+    /// Compute the full middleware chain and the pattern of every route
+    pub(crate) fn freeze_routes(&mut self) {
+        let middlewares = &self.middlewares;
+
+        for route in self.route.routes_mut().chain(self.slash_route.routes_mut()) {
+            route.chain = Some(RouteChain {
+                middlewares: middlewares
+                    .iter()
+                    .chain(route.middlewares.iter())
+                    .cloned()
+                    .collect(),
+                pattern: Arc::from(route.path.as_str()),
+            });
+        }
+    }
+
+    /// Resource registered with the provided trailing slash form
+    fn resource_mut(&mut self, trailing_slash: bool) -> &mut Resource {
+        if trailing_slash {
+            &mut self.slash_route
+        } else {
+            &mut self.route
+        }
     }
 }
 
 pub struct RouteValueResult {
     route_value: RouteValue,
     params: HashMap<String, String>,
+    trailing_slash: bool,
+    policy: TrailingSlash,
 }
 
 impl RouteValueResult {
-    pub fn new(route_value: RouteValue, params: HashMap<String, String>) -> Self {
+    pub fn new(
+        route_value: RouteValue,
+        params: HashMap<String, String>,
+        trailing_slash: bool,
+        policy: TrailingSlash,
+    ) -> Self {
         RouteValueResult {
             route_value,
             params,
+            trailing_slash,
+            policy,
         }
     }
 
     pub fn get_route(&self, method: &Method) -> Option<&Route> {
-        self.route_value.route.get_route(method)
+        self.get_routes(method).next()
+    }
+
+    /// Candidate routes of the method in the order they are tried
+    pub fn get_routes(&self, method: &Method) -> impl Iterator<Item = &Route> {
+        self.get_resources().get_routes(method)
+    }
+
+    /// First candidate route accepted by all of its guards
+    pub fn select_route(&self, req: &Parts) -> Result<&Route, StatusCode> {
+        self.get_resources().select_route(req)
+    }
+
+    /// Redirect status if the method is only registered with the other trailing slash form
+    pub fn get_redirect(&self, method: &Method) -> Option<StatusCode> {
+        self.get_resources().get_redirect(method)
+    }
+
+    fn get_resources(&self) -> PathResources<'_> {
+        self.route_value
+            .get_resources(self.trailing_slash, self.policy)
     }
 
     pub fn get_middlewares(&self) -> &Vec<Arc<dyn Middleware>> {
@@ -56,15 +180,27 @@ impl RouteValueResult {
 #[derive(Clone, Debug)]
 pub struct RouteTrie {
     head: Node,
+    trailing_slash: TrailingSlash,
 }
 
 impl RouteTrie {
     pub fn new() -> Self {
         RouteTrie {
             head: Node::new("/".to_string(), None),
+            trailing_slash: TrailingSlash::default(),
         }
     }
 
+    /// Set the policy for paths differing only by a trailing slash
+    /// Routes of both forms are kept apart unless the policy is `Ignore`
+    /// Panic if the policy is `Ignore` and the same method is registered for both forms of a path
+    pub fn set_trailing_slash(&mut self, policy: TrailingSlash) {
+        let split = policy != TrailingSlash::Ignore;
+
+        self.trailing_slash = policy;
+        self.head.for_each_value_mut(&mut |val| val.regroup(split));
+    }
+
     /// Insert middleware into root node
     pub fn insert_default_middleware(&mut self, middleware: impl Middleware) {
         match &mut self.head.value {
@@ -81,60 +217,28 @@ impl RouteTrie {
     }
 
     /// Insert route values into the trie
+    /// Return the inserted route for further configuration
     /// Panic if ambigous definition is detected
-    pub fn insert_route(&mut self, path: &str, route: Route) {
+    pub fn insert_route(&mut self, path: &str, mut route: Route) -> &mut Route {
         // Split path string and drop additional '/'
         let mut split_key = path.split('/').filter(|key| !key.is_empty()).peekable();
 
-        split_key.clone().enumerate().for_each(|(pos, x)| {
-            if x.contains('*') {
-                if x.len() != 1 {
-                    panic!("ERROR: Consisting * in route name at: {}", path);
-                } else if pos != split_key.clone().count() - 1 {
-                    panic!("ERROR: * must be in the last at: {}", path);
-                }
-            }
-        });
+        validate_path(path);
 
-        let mut curr_node = &mut self.head;
+        route.path = route_pattern(path);
 
         // if the path is "/"
         if split_key.peek().is_none() {
-            self.insert_default_route(route);
-            return;
+            return self.insert_default_route(route);
         }
 
-        while let Some(k) = split_key.next() {
+        // Both forms are the same route unless the policy tells them apart
+        let trailing_slash = self.trailing_slash != TrailingSlash::Ignore && path.ends_with('/');
+        let mut curr_node = &mut self.head;
+
+        for k in split_key {
             match curr_node.process_insertion(k) {
                 Ok(next_node) => {
-                    if split_key.peek().is_none() {
-                        match &mut next_node.value {
-                            Some(val) => {
-                                if let Some(duplicated) =
-                                    val.route.add_route(route.method.clone(), route)
-                                {
-                                    panic!(
-                                        "Duplicated route method '{}' at '{}' detected",
-                                        duplicated.method, path
-                                    );
-                                }
-                            }
-                            None => {
-                                let mut next_node_val = RouteValue::default();
-                                if let Some(duplicated) =
-                                    next_node_val.route.add_route(route.method.clone(), route)
-                                {
-                                    panic!(
-                                        "Duplicated route method '{}' at '{}' detected",
-                                        duplicated.method, path
-                                    );
-                                }
-
-                                next_node.value = Some(next_node_val);
-                            }
-                        }
-                        break;
-                    }
                     curr_node = next_node;
                 }
                 Err(err) => {
@@ -142,6 +246,11 @@ impl RouteTrie {
                 }
             }
         }
+
+        curr_node
+            .value
+            .get_or_insert_with(RouteValue::default)
+            .add_route(trailing_slash, route)
     }
 
     /// Insert middleware into specific node
@@ -150,15 +259,13 @@ impl RouteTrie {
         let split_key = path.split('/');
         let mut split_key = split_key.filter(|key| !key.is_empty()).peekable();
 
-        split_key.clone().enumerate().for_each(|(pos, key)| {
-            if key.contains('*') {
-                if key.len() != 1 {
-                    panic!("ERROR: Consisting * in route name at: {}", path);
-                } else if pos != split_key.clone().count() - 1 {
-                    panic!("ERROR: * must be in the last at: {}", path);
-                }
-            }
-        });
+        validate_path(path);
+
+        // if the path is "/"
+        if split_key.peek().is_none() {
+            self.insert_default_middleware(middleware);
+            return;
+        }
 
         let mut curr_node = &mut self.head;
 
@@ -190,22 +297,21 @@ impl RouteTrie {
 
     /// Search node through the provided key
     /// Middleware will be accumulated throughout the search path
+    /// The path is normalized and percent-decoded before matching
+    /// Trailing slash is resolved according to the trie policy
     pub fn search_route(&self, path: &str) -> Option<RouteValueResult> {
-        // Split key and drop additional '/'
-        let split_key = path.split('/');
-        let mut split_key = split_key
-            .filter(|key| !key.is_empty())
-            .collect::<Vec<&str>>();
+        let segments = match normalize_path(path) {
+            Ok(segments) => segments,
+            Err(_) => return None,
+        };
+        let mut split_key = segments.iter().map(|x| x.as_str()).collect::<Vec<&str>>();
 
         let mut curr_node = &self.head;
         let mut params = HashMap::default();
         let mut middlewares = vec![];
 
-        match &curr_node.value {
-            Some(val) => {
-                middlewares.append(&mut val.middlewares.clone());
-            }
-            None => {}
+        if let Some(val) = &curr_node.value {
+            middlewares.append(&mut val.middlewares.clone());
         }
 
         if !split_key.is_empty() {
@@ -220,11 +326,20 @@ impl RouteTrie {
             }
         }
 
+        // Wildcard consumes the rest of the path including the trailing slash
+        let trailing_slash = !segments.is_empty() && curr_node.key != "*" && path.ends_with('/');
+
         match &curr_node.value {
             Some(val) => {
-                let route_val = RouteValue::new(middlewares, val.route.clone());
-
-                Some(RouteValueResult::new(route_val, params))
+                let route_val =
+                    RouteValue::new(middlewares, val.route.clone(), val.slash_route.clone());
+
+                Some(RouteValueResult::new(
+                    route_val,
+                    params,
+                    trailing_slash,
+                    self.trailing_slash,
+                ))
             }
             None => None,
         }
@@ -235,40 +350,31 @@ impl RouteTrie {
     ///
     /// For example, /src/ -> /des/ with 'example' key path
     /// src will be located at /des/example/src/
+    ///
+    /// Existing nodes of des are merged with src, routes are combined method by method
+    /// and middlewares of src are applied after the middlewares of des.
+    /// Panic if the same method is registered on the same path in both tries
     pub fn insert_sub_route(des: &mut Self, path: &str, src: Self) {
         // Split key and drop additional '/'
         let split_key = path.split('/');
-        let mut split_key = split_key.filter(|key| !key.is_empty()).peekable();
+        let split_key = split_key.filter(|key| !key.is_empty());
 
-        split_key.clone().enumerate().for_each(|(pos, x)| {
-            if x.contains('*') {
-                if x.len() != 1 {
-                    panic!("ERROR: Consisting * in route name at: {}", path);
-                } else if pos != split_key.clone().count() - 1 {
-                    panic!("ERROR: * must be in the last at: {}", path);
-                }
-            }
-        });
+        validate_path(path);
+
+        let mut src = src;
+
+        // Routes of src follow the trailing slash policy of des
+        src.set_trailing_slash(des.trailing_slash);
 
         let mut curr_node = &mut des.head;
 
-        if split_key.peek().is_none() {
-            des.head = src.head;
-            return;
+        if path.split('/').any(|key| !key.is_empty()) {
+            src.head.prefix_route_paths(&route_pattern(path));
         }
 
-        while let Some(k) = split_key.next() {
+        for k in split_key {
             match curr_node.process_insertion(k) {
                 Ok(next_node) => {
-                    if split_key.peek().is_none() {
-                        if next_node.value.is_some() || !next_node.child_nodes.is_empty() {
-                            panic!("There is conflict between main router and sub router at '{}'. Make sure main router does not consist any routing data in '{}'.", path, path);
-                        }
-
-                        next_node.value = src.head.value;
-                        next_node.child_nodes = src.head.child_nodes;
-                        break;
-                    }
                     curr_node = next_node;
                 }
                 Err(err) => {
@@ -276,31 +382,137 @@ impl RouteTrie {
                 }
             }
         }
+
+        curr_node.merge(src.head, path);
     }
 
-    fn insert_default_route(&mut self, route: Route) {
-        match &mut self.head.value {
-            Some(val) => {
-                if let Some(duplicated) = val.route.add_route(route.method.clone(), route) {
-                    panic!(
-                        "Duplicated route method '{}' at '/' detected",
-                        duplicated.method
-                    );
-                }
-            }
-            None => {
-                let mut val = RouteValue::default();
-                if let Some(duplicated) = val.route.add_route(route.method.clone(), route) {
-                    panic!(
-                        "Duplicated route method '{}' at '/' detected",
-                        duplicated.method
-                    );
+    fn insert_default_route(&mut self, route: Route) -> &mut Route {
+        let method = route.method.clone();
+        let resource = &mut self
+            .head
+            .value
+            .get_or_insert_with(RouteValue::default)
+            .route;
+
+        match resource.add_route(method, route) {
+            Ok(route) => route,
+            Err(duplicated) => panic!("Duplicated route method '{}' at '/' detected", duplicated),
+        }
+    }
+
+    /// Apply the function to every route registered in the trie
+    pub fn for_each_route_mut(&mut self, mut f: impl FnMut(&mut Route)) {
+        self.head.for_each_route_mut(&mut f);
+    }
+
+    /// Move the middlewares of every node onto the routes under it
+    /// The routes keep their middlewares wherever the trie is merged afterwards
+    pub fn move_middlewares_to_routes(&mut self) {
+        self.head.move_middlewares_to_routes(&[]);
+    }
+
+    /// Drop the routes and middlewares registered on the root path
+    pub fn remove_root(&mut self) {
+        self.head.value = None;
+    }
+
+    /// Route values of the paths with routes paired with their route pattern
+    /// Middlewares of the values are the full chains applied to the path
+    pub fn route_values(&self) -> Vec<(String, RouteValue)> {
+        let mut values = vec![];
+
+        self.head.collect_values(&mut values);
+
+        values
+            .into_iter()
+            .filter_map(|val| {
+                let pattern = val
+                    .route
+                    .routes()
+                    .chain(val.slash_route.routes())
+                    .next()?
+                    .path
+                    .clone();
+                let middlewares = self
+                    .search_route(&pattern)
+                    .map(|result| result.get_middlewares().clone())
+                    .unwrap_or_default();
+
+                Some((
+                    pattern,
+                    RouteValue::new(middlewares, val.route.clone(), val.slash_route.clone()),
+                ))
+            })
+            .collect()
+    }
+
+    pub fn trailing_slash(&self) -> TrailingSlash {
+        self.trailing_slash
+    }
+
+    /// Describe all routes registered in the trie
+    /// Middlewares are listed in the order they are applied to the route
+    pub fn route_table(&self) -> RouteTable {
+        let mut routes = vec![];
+
+        self.head.collect_routes(&mut routes);
+
+        let routes = routes
+            .into_iter()
+            .map(|route| {
+                let middlewares = match self.search_route(&route.path) {
+                    Some(result) => result
+                        .get_middlewares()
+                        .iter()
+                        .chain(route.middlewares.iter())
+                        .map(|middleware| middleware.name())
+                        .collect(),
+                    None => vec![],
+                };
+
+                RouteInfo {
+                    method: route.method.clone(),
+                    path: route.path.clone(),
+                    name: route.name.clone(),
+                    middlewares,
                 }
+            })
+            .collect();
 
-                self.head.value = Some(val);
-            }
-        }
+        RouteTable::new(routes)
+    }
+
+    /// Collect the names of the routes registered in the trie
+    /// Panic if a name is registered more than once
+    pub fn route_names(&self) -> RouteNames {
+        let mut route_names = RouteNames::default();
+
+        self.head.collect_route_names(&mut route_names);
+
+        route_names
+    }
+}
+
+/// Panic if the path breaks the route path rules shared with the route attributes
+fn validate_path(path: &str) {
+    if let Err(message) = obsidian_path::validate(path) {
+        panic!("ERROR: {}", message);
+    }
+}
+
+/// Normalized route pattern with the leading slash and the trailing slash form kept
+fn route_pattern(path: &str) -> String {
+    let segments = path
+        .split('/')
+        .filter(|key| !key.is_empty())
+        .collect::<Vec<&str>>();
+    let mut pattern = format!("/{}", segments.join("/"));
+
+    if !segments.is_empty() && path.ends_with('/') {
+        pattern.push('/');
     }
+
+    pattern
 }
 
 #[derive(Clone, Debug)]
@@ -319,6 +531,108 @@ impl Node {
         }
     }
 
+    /// Merge the value and children of the other node into this node
+    /// Children are inserted key by key, so partially matched keys are split as usual
+    fn merge(&mut self, other: Node, path: &str) {
+        if let Some(other_val) = other.value {
+            self.value
+                .get_or_insert_with(RouteValue::default)
+                .merge(other_val);
+        }
+
+        for child in other.child_nodes {
+            match self.process_insertion(&child.key) {
+                Ok(next_node) => next_node.merge(child, path),
+                Err(err) => {
+                    panic!("SubRouter: {} at {}", err, path);
+                }
+            }
+        }
+    }
+
+    /// Prepend the mount prefix to the patterns of all routes under this node
+    fn prefix_route_paths(&mut self, prefix: &str) {
+        self.for_each_route_mut(&mut |route| {
+            route.path = match route.path.as_str() {
+                "/" => prefix.to_string(),
+                path => format!("{}{}", prefix.trim_end_matches('/'), path),
+            };
+        });
+    }
+
+    fn for_each_route_mut(&mut self, f: &mut impl FnMut(&mut Route)) {
+        if let Some(val) = &mut self.value {
+            val.route
+                .routes_mut()
+                .chain(val.slash_route.routes_mut())
+                .for_each(&mut *f);
+        }
+
+        for child in self.child_nodes.iter_mut() {
+            child.for_each_route_mut(f);
+        }
+    }
+
+    fn for_each_value_mut(&mut self, f: &mut impl FnMut(&mut RouteValue)) {
+        if let Some(val) = &mut self.value {
+            f(val);
+        }
+
+        for child in self.child_nodes.iter_mut() {
+            child.for_each_value_mut(f);
+        }
+    }
+
+    fn move_middlewares_to_routes(&mut self, inherited: &[Arc<dyn Middleware>]) {
+        let mut middlewares = inherited.to_vec();
+
+        if let Some(val) = &mut self.value {
+            middlewares.append(&mut val.middlewares);
+
+            for route in val.route.routes_mut().chain(val.slash_route.routes_mut()) {
+                route.middlewares.splice(0..0, middlewares.iter().cloned());
+            }
+        }
+
+        for child in self.child_nodes.iter_mut() {
+            child.move_middlewares_to_routes(&middlewares);
+        }
+    }
+
+    fn collect_values<'a>(&'a self, values: &mut Vec<&'a RouteValue>) {
+        if let Some(val) = &self.value {
+            values.push(val);
+        }
+
+        for child in self.child_nodes.iter() {
+            child.collect_values(values);
+        }
+    }
+
+    fn collect_routes<'a>(&'a self, routes: &mut Vec<&'a Route>) {
+        if let Some(val) = &self.value {
+            routes.extend(val.route.routes().chain(val.slash_route.routes()));
+        }
+
+        for child in self.child_nodes.iter() {
+            child.collect_routes(routes);
+        }
+    }
+
+    fn collect_route_names(&self, route_names: &mut RouteNames) {
+        if let Some(val) = &self.value {
+            for route in val.route.routes().chain(val.slash_route.routes()) {
+                if let Some(name) = &route.name {
+                    route_names.insert(name, &route.path);
+                }
+            }
+        }
+
+        for child in self.child_nodes.iter() {
+            child.collect_route_names(route_names);
+        }
+    }
+
     fn is_param(&self) -> bool {
         self.key.chars().next().unwrap_or(' ') == ':'
     }
@@ -332,7 +646,7 @@ impl Node {
                 let new_node = Self::new(key.to_string(), None);
 
                 match key {
-                    k if k == "*" => {
+                    "*" => {
                         self.child_nodes.push(new_node);
 
                         if let Some(node) = self.child_nodes.last_mut() {
@@ -368,8 +682,8 @@ impl Node {
                     let mut inter_node = Self::new(child_key, None);
 
                     // Move out the previous child and transfer to intermediate node
-                    inter_node.child_nodes = std::mem::replace(&mut node.child_nodes, vec![]);
-                    inter_node.value = std::mem::replace(&mut node.value, None);
+                    inter_node.child_nodes = std::mem::take(&mut node.child_nodes);
+                    inter_node.value = node.value.take();
 
                     node.child_nodes.insert(0, inter_node);
 
@@ -452,7 +766,7 @@ impl Node {
         &self,
         key: &mut Vec<&str>,
         params: &mut HashMap<String, String>,
-        middlewares: &mut Vec<std::sync::Arc<(dyn Middleware + 'static)>>,
+        middlewares: &mut Vec<Arc<dyn Middleware>>,
         is_break_parent: bool,
     ) -> Option<&Self> {
         let curr_key = key.remove(0);
@@ -479,11 +793,8 @@ impl Node {
                             Some(final_val) => {
                                 params.insert(node.key[1..].to_string(), curr_key.to_string());
 
-                                match &node.value {
-                                    Some(curr_val) => {
-                                        middlewares.append(&mut curr_val.middlewares.clone());
-                                    }
-                                    None => {}
+                                if let Some(curr_val) = &node.value {
+                                    middlewares.append(&mut curr_val.middlewares.clone());
                                 }
 
                                 return Some(final_val);
@@ -497,11 +808,8 @@ impl Node {
 
                 // Check wildcard
                 if node.key == "*" {
-                    match &node.value {
-                        Some(curr_val) => {
-                            middlewares.append(&mut curr_val.middlewares.clone());
-                        }
-                        None => {}
+                    if let Some(curr_val) = &node.value {
+                        middlewares.append(&mut curr_val.middlewares.clone());
                     }
 
                     return Some(node);
@@ -635,7 +943,7 @@ mod tests {
                 assert!(route_value);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
     }
@@ -665,7 +973,7 @@ mod tests {
                 assert!(route_value);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
 
@@ -682,7 +990,7 @@ mod tests {
                 assert!(route_value);
             }
             _ => {
-                assert!(false);
+                unreachable!();
             }
         }
     }
@@ -717,7 +1025,7 @@ mod tests {
         route_trie.insert_middleware("/noral/test/", logger2);
         route_trie.insert_middleware("/ノーマル/テーブル/", logger3);
 
-        let test_cases = vec![
+        let test_cases = [
             ("/normal/test/", 1),
             ("/noral/test/", 2),
             ("/ノーマル/テスト/", 1),
@@ -738,7 +1046,7 @@ mod tests {
                     assert!(route_value);
                 }
                 _ => {
-                    assert!(false);
+                    unreachable!();
                 }
             }
         }
@@ -757,7 +1065,7 @@ mod tests {
         route_trie.insert_middleware("/normal/test/*", logger2);
         route_trie.insert_middleware("/normal/test/*", logger3);
 
-        let test_cases = vec![
+        let test_cases = [
             "/normal/test/test",
             "/normal/test/123",
             "/normal/test/こんにちは",
@@ -778,10 +1086,143 @@ mod tests {
                     assert!(route_value);
                 }
                 _ => {
-                    assert!(false);
+                    unreachable!();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn radix_trie_percent_decode_test() {
+        let mut route_trie = RouteTrie::new();
+        let handler = |_x| "test";
+
+        route_trie.insert_route("/users/:id", Route::new(Method::GET, handler));
+        route_trie.insert_route("/ノーマル/テスト/", Route::new(Method::GET, handler));
+
+        let test_cases = [
+            ("/users/John%20Doe", "John Doe"),
+            ("/users/%E3%83%86%E3%82%B9%E3%83%88", "テスト"),
+            ("/users/a%2Fb", "a/b"),
+            ("//users/./John", "John"),
+            ("/normal/../users/John", "John"),
+        ];
+
+        for case in test_cases.iter() {
+            let result = route_trie.search_route(case.0);
+
+            assert!(result.is_some());
+
+            match result {
+                Some(route) => {
+                    let params = route.get_params();
+
+                    assert_eq!(params.get("id").unwrap(), case.1);
+                    assert!(route.get_route(&Method::GET).is_some());
+                }
+                _ => {
+                    unreachable!();
                 }
             }
         }
+
+        let result = route_trie
+            .search_route("/%E3%83%8E%E3%83%BC%E3%83%9E%E3%83%AB/%E3%83%86%E3%82%B9%E3%83%88");
+
+        assert!(result.is_some());
+
+        assert!(route_trie.search_route("/users/a/b").is_none());
+        assert!(route_trie.search_route("/../users/John").is_none());
+        assert!(route_trie.search_route("/users/%FF").is_none());
+    }
+
+    #[test]
+    fn radix_trie_trailing_slash_test() {
+        let handler = |_x| "test";
+        let policies = [
+            (TrailingSlash::Ignore, true, true, None),
+            (TrailingSlash::Strict, true, false, None),
+            (
+                TrailingSlash::Redirect(StatusCode::MOVED_PERMANENTLY),
+                true,
+                false,
+                Some(StatusCode::MOVED_PERMANENTLY),
+            ),
+        ];
+
+        for policy in policies.iter() {
+            let mut route_trie = RouteTrie::new();
+
+            route_trie.set_trailing_slash(policy.0);
+            route_trie.insert_route("/normal/test", Route::new(Method::GET, handler));
+            route_trie.insert_route("/files/*", Route::new(Method::GET, handler));
+
+            let exact = route_trie.search_route("/normal/test").unwrap();
+            let slash = route_trie.search_route("/normal/test/").unwrap();
+            let wildcard = route_trie.search_route("/files/test/").unwrap();
+
+            assert_eq!(exact.get_route(&Method::GET).is_some(), policy.1);
+            assert_eq!(slash.get_route(&Method::GET).is_some(), policy.2);
+            assert_eq!(slash.get_redirect(&Method::GET), policy.3);
+            assert_eq!(slash.get_redirect(&Method::POST), None);
+            assert!(wildcard.get_route(&Method::GET).is_some());
+        }
+    }
+
+    #[test]
+    fn radix_trie_strict_slash_test() {
+        let mut route_trie = RouteTrie::new();
+        let handler = |_x| "test";
+
+        route_trie.set_trailing_slash(TrailingSlash::Strict);
+        route_trie.insert_route("/normal/test", Route::new(Method::GET, handler));
+        route_trie.insert_route("/normal/test/", Route::new(Method::GET, handler));
+
+        let exact = route_trie.search_route("/normal/test").unwrap();
+        let slash = route_trie.search_route("/normal/test/").unwrap();
+
+        assert!(exact.get_route(&Method::GET).is_some());
+        assert!(slash.get_route(&Method::GET).is_some());
+    }
+
+    #[should_panic(expected = "Duplicated route method 'GET' at '/normal/test/' detected")]
+    #[test]
+    fn radix_trie_ignore_slash_duplicate_test() {
+        let mut route_trie = RouteTrie::new();
+        let handler = |_x| "test";
+
+        route_trie.insert_route("/normal/test", Route::new(Method::GET, handler));
+        route_trie.insert_route("/normal/test/", Route::new(Method::GET, handler));
+    }
+
+    #[test]
+    fn radix_trie_regroup_slash_test() {
+        let mut route_trie = RouteTrie::new();
+        let handler = |_x| "test";
+
+        route_trie.insert_route("/normal/test/", Route::new(Method::GET, handler));
+        route_trie.set_trailing_slash(TrailingSlash::Strict);
+
+        let exact = route_trie.search_route("/normal/test").unwrap();
+        let slash = route_trie.search_route("/normal/test/").unwrap();
+
+        assert!(exact.get_route(&Method::GET).is_none());
+        assert!(slash.get_route(&Method::GET).is_some());
+
+        route_trie.set_trailing_slash(TrailingSlash::Ignore);
+
+        let exact = route_trie.search_route("/normal/test").unwrap();
+
+        assert!(exact.get_route(&Method::GET).is_some());
+    }
+
+    #[should_panic(expected = "ERROR: Missing param name in route at: /users/:")]
+    #[test]
+    fn radix_trie_missing_param_name_test() {
+        let mut route_trie = RouteTrie::new();
+        let handler = |_x| "test";
+
+        route_trie.insert_route("/users/:", Route::new(Method::GET, handler));
     }
 
     #[should_panic]
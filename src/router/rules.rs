@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::path::wildcard_segments;
+use super::route_names::fill_pattern;
+use super::{normalize_path, Handler, ResponseResult};
+use crate::context::Context;
+use crate::header::LOCATION;
+use crate::{Body, ObsidianError, Response, StatusCode};
+
+/// PathPattern matches the request path against a route pattern outside of the router
+///
+/// `:name` captures a segment and a trailing `*` captures the rest of the path as `*`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathPattern {
+    segments: Vec<String>,
+}
+
+impl PathPattern {
+    /// Error if `*` is not the last segment
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let segments = pattern
+            .split('/')
+            .filter(|key| !key.is_empty())
+            .map(|key| key.to_string())
+            .collect::<Vec<String>>();
+
+        for (pos, segment) in segments.iter().enumerate() {
+            if segment.contains('*') && (segment.len() != 1 || pos != segments.len() - 1) {
+                return Err(format!("* must be the last segment at: {}", pattern));
+            }
+        }
+
+        Ok(PathPattern { segments })
+    }
+
+    /// Captured params of the normalized path, the rest of the wildcard is keyed by `*`
+    pub fn matches(&self, path: &str) -> Option<HashMap<String, String>> {
+        let path = normalize_path(path).ok()?;
+        let mut params = HashMap::default();
+        let mut path_segments = path.iter();
+
+        for segment in self.segments.iter() {
+            if segment == "*" {
+                let rest = path_segments.map(|x| x.as_str()).collect::<Vec<&str>>();
+                params.insert("*".to_string(), rest.join("/"));
+
+                return Some(params);
+            }
+
+            let value = path_segments.next()?;
+
+            if let Some(key) = segment.strip_prefix(':') {
+                params.insert(key.to_string(), value.clone());
+            } else if segment != value {
+                return None;
+            }
+        }
+
+        if path_segments.next().is_some() {
+            return None;
+        }
+
+        Some(params)
+    }
+
+    /// Whether the param or `*` for the wildcard is captured by the pattern
+    fn captures(&self, key: &str) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| match segment.strip_prefix(':') {
+                Some(param) => param == key,
+                None => segment == "*" && key == "*",
+            })
+    }
+}
+
+/// Check that the target only refers to the params captured by the source pattern
+fn validate_target(from: &PathPattern, to: &str) -> Result<(), String> {
+    for segment in target_path(to).split('/') {
+        let key = match segment.strip_prefix(':') {
+            Some(key) => key,
+            None if segment == "*" => segment,
+            None => continue,
+        };
+
+        if !from.captures(key) {
+            return Err(format!(
+                "Param {} of {} is not captured by the source",
+                key, to
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Path part of the target, the origin of an absolute target is kept as it is
+fn split_origin(to: &str) -> (&str, &str) {
+    match to.find("://") {
+        Some(scheme_end) => match to[scheme_end + 3..].find('/') {
+            Some(path_start) => to.split_at(scheme_end + 3 + path_start),
+            None => (to, "/"),
+        },
+        None => ("", to),
+    }
+}
+
+fn target_path(to: &str) -> &str {
+    split_origin(to).1
+}
+
+/// Fill the params of the target, the query of the request is kept if the target has none
+fn fill_target(to: &str, params: &HashMap<String, String>, query: Option<&str>) -> Option<String> {
+    let (origin, path) = split_origin(to);
+    let mut target = format!("{}{}", origin, fill_pattern(path, params).ok()?);
+
+    if let Some(query) = query {
+        if !to.contains('?') {
+            target.push('?');
+            target.push_str(query);
+        }
+    }
+
+    Some(target)
+}
+
+/// Redirect responds with the location filled with the params of the matched route
+///
+/// Params are the ones captured by the router and `*` is the rest of the path of a wildcard route,
+/// so the redirect keeps working under the prefix of the routers it is merged into.
+pub struct Redirect {
+    to: String,
+    status: StatusCode,
+}
+
+impl Redirect {
+    /// Panic if the status is not a redirection or the target refers to params not in the source
+    pub fn new(from: &str, to: &str, status: StatusCode) -> Self {
+        Redirect::parse(from, to, status).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    fn parse(from: &str, to: &str, status: StatusCode) -> Result<Self, String> {
+        if !status.is_redirection() {
+            return Err(format!(
+                "Redirect requires a redirection status, found {}",
+                status
+            ));
+        }
+
+        validate_target(&PathPattern::parse(from)?, to)?;
+
+        Ok(Redirect {
+            to: to.to_string(),
+            status,
+        })
+    }
+}
+
+impl Handler for Redirect {
+    fn call(&self, ctx: Context) -> ResponseResult {
+        let pattern = ctx.route_pattern().unwrap_or("/");
+        let location = wildcard_segments(pattern, ctx.uri().path())
+            .ok()
+            .and_then(|rest| {
+                let mut params = ctx.params().clone();
+
+                if pattern.ends_with('*') {
+                    params.insert("*".to_string(), rest.join("/"));
+                }
+
+                fill_target(&self.to, &params, ctx.uri().query())
+            });
+
+        match location {
+            Some(location) => Response::builder()
+                .status(self.status)
+                .header(LOCATION, location)
+                .body(Body::empty()),
+            None => Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::from("404 Not Found")),
+        }
+    }
+}
+
+/// RewriteRule changes the request path before the route is searched
+#[derive(Clone, Debug)]
+pub struct RewriteRule {
+    from: PathPattern,
+    to: String,
+}
+
+impl RewriteRule {
+    /// Panic if the target refers to params not in the source
+    pub fn new(from: &str, to: &str) -> Self {
+        RewriteRule::parse(from, to).unwrap_or_else(|err| panic!("ERROR: {}", err))
+    }
+
+    fn parse(from: &str, to: &str) -> Result<Self, String> {
+        let from = PathPattern::parse(from)?;
+        validate_target(&from, to)?;
+
+        Ok(RewriteRule {
+            from,
+            to: to.to_string(),
+        })
+    }
+
+    /// Rewritten path and query, None if the path does not match the rule
+    pub fn rewrite(&self, path: &str, query: Option<&str>) -> Option<String> {
+        let params = self.from.matches(path)?;
+
+        fill_target(target_path(&self.to), &params, query)
+    }
+}
+
+/// Rules are the redirects and rewrites defined in a rules file
+///
+/// Each line is either `redirect <status> <from> <to>` or `rewrite <from> <to>`,
+/// empty lines and lines starting with `#` are ignored.
+///
+/// ```text
+/// # Moved articles
+/// redirect 301 /old/:id /new/:id
+/// rewrite /en/* /*
+/// ```
+#[derive(Default)]
+pub struct Rules {
+    pub(crate) redirects: Vec<(String, Redirect)>,
+    pub(crate) rewrites: Vec<RewriteRule>,
+}
+
+impl Rules {
+    /// Error with the line number if a rule is invalid
+    pub fn parse(rules: &str) -> Result<Self, ObsidianError> {
+        let mut parsed = Rules::default();
+
+        for (index, line) in rules.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+
+            let result = match fields.as_slice() {
+                ["redirect", status, from, to] => status
+                    .parse::<u16>()
+                    .ok()
+                    .and_then(|status| StatusCode::from_u16(status).ok())
+                    .ok_or_else(|| format!("Invalid status {}", status))
+                    .and_then(|status| Redirect::parse(from, to, status))
+                    .map(|redirect| parsed.redirects.push((from.to_string(), redirect))),
+                ["rewrite", from, to] => {
+                    RewriteRule::parse(from, to).map(|rewrite| parsed.rewrites.push(rewrite))
+                }
+                _ => Err(format!("Unknown rule {}", line)),
+            };
+
+            result.map_err(|err| {
+                ObsidianError::GeneralError(format!("Rules line {}: {}", index + 1, err))
+            })?;
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ObsidianError> {
+        let rules =
+            fs::read_to_string(path).map_err(|err| ObsidianError::GeneralError(err.to_string()))?;
+
+        Rules::parse(&rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_pattern_matches_test() {
+        let pattern = PathPattern::parse("/old/:id/files/*").unwrap();
+
+        let params = pattern.matches("/old/1/files/a/b%20c").unwrap();
+
+        assert_eq!(params.get("id").unwrap(), "1");
+        assert_eq!(params.get("*").unwrap(), "a/b c");
+        assert!(pattern.matches("/old/1").is_none());
+        assert!(PathPattern::parse("/old/*/raw").is_err());
+    }
+
+    #[test]
+    fn rewrite_rule_test() {
+        let rule = RewriteRule::new("/en/*", "/*");
+
+        assert_eq!(
+            rule.rewrite("/en/articles/1", Some("page=2")).unwrap(),
+            "/articles/1?page=2"
+        );
+        assert_eq!(rule.rewrite("/en", None).unwrap(), "/");
+        assert!(rule.rewrite("/fr/articles", None).is_none());
+    }
+
+    #[test]
+    fn rules_parse_test() {
+        let rules = Rules::parse(
+            "# Moved articles\n\
+             redirect 301 /old/:id https://example.com/new/:id\n\
+             \n\
+             rewrite /legacy/:id /articles/:id\n",
+        )
+        .unwrap();
+
+        assert_eq!(rules.redirects.len(), 1);
+        assert_eq!(rules.rewrites.len(), 1);
+
+        assert!(Rules::parse("redirect 200 /old /new").is_err());
+        assert!(Rules::parse("rewrite /old/:id /new/:name").is_err());
+        assert!(Rules::parse("move /old /new").is_err());
+    }
+
+    #[should_panic]
+    #[test]
+    fn redirect_invalid_target_test() {
+        Redirect::new("/old", "/new/:id", StatusCode::MOVED_PERMANENTLY);
+    }
+}
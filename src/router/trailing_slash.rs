@@ -0,0 +1,13 @@
+use crate::StatusCode;
+
+/// Policy for request paths which differ from the registered route only by a trailing slash
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TrailingSlash {
+    /// `/a/` and `/a` resolve to the same route
+    #[default]
+    Ignore,
+    /// `/a/` and `/a` are different routes
+    Strict,
+    /// Redirect to the registered form with the provided status, either 301 or 308
+    Redirect(StatusCode),
+}
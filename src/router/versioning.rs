@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use futures::Future;
+use hyper::http::request::Parts;
+
+use super::Guard;
+use crate::app::EndpointExecutor;
+use crate::context::Context;
+use crate::header::{HeaderName, HeaderValue, ACCEPT};
+use crate::middleware::Middleware;
+use crate::{Body, Response, StatusCode};
+
+/// Where the requested API version is read from
+#[derive(Clone, Debug, PartialEq)]
+pub enum VersionStrategy {
+    /// Leading path segment, for example `/v2/users`
+    Prefix,
+    /// Header value, for example `Api-Version: 2`
+    Header(HeaderName),
+    /// Vendor media type in `Accept`, for example `application/vnd.acme.v2+json` for vendor `acme`
+    MediaType(String),
+}
+
+impl VersionStrategy {
+    /// Panic if the name is not a valid header
+    pub fn header(name: &str) -> Self {
+        VersionStrategy::Header(
+            HeaderName::from_bytes(name.as_bytes()).expect("Invalid header name for versioning"),
+        )
+    }
+
+    pub fn media_type(vendor: &str) -> Self {
+        VersionStrategy::MediaType(vendor.to_ascii_lowercase())
+    }
+}
+
+/// Versioning selects the routes of the requested API version
+///
+/// Requests without a version are served by the default version. With the prefix
+/// strategy the routes of the default version are also registered without the prefix,
+/// except the root route of the version.
+/// Responses of deprecated versions carry the `Deprecation` and optional `Sunset` headers.
+///
+/// # Example
+///
+/// ```
+/// # use obsidian::router::{Router, Versioning, VersionStrategy};
+/// let versioning = Versioning::new(VersionStrategy::header("api-version"))
+///     .default_version(2)
+///     .deprecate(1, Some("Sat, 31 Oct 2026 23:59:59 GMT"));
+///
+/// let mut router = Router::new();
+///
+/// router.version(&versioning, 1, |r| {
+///     r.get("/users", |_ctx| "users v1");
+/// });
+///
+/// router.version(&versioning, 2, |r| {
+///     r.get("/users", |_ctx| "users v2");
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct Versioning {
+    strategy: VersionStrategy,
+    default: Option<u32>,
+    deprecated: HashMap<u32, Option<HeaderValue>>,
+}
+
+impl Versioning {
+    pub fn new(strategy: VersionStrategy) -> Self {
+        Versioning {
+            strategy,
+            default: None,
+            deprecated: HashMap::default(),
+        }
+    }
+
+    /// Version serving the requests which do not specify one
+    pub fn default_version(mut self, version: u32) -> Self {
+        self.default = Some(version);
+        self
+    }
+
+    /// Mark the version as deprecated with the optional HTTP date of its removal
+    /// Panic if the sunset date is not a valid header value
+    pub fn deprecate(mut self, version: u32, sunset: Option<&str>) -> Self {
+        let sunset = sunset.map(|sunset| {
+            HeaderValue::from_str(sunset).expect("Invalid sunset date for versioning")
+        });
+
+        self.deprecated.insert(version, sunset);
+        self
+    }
+
+    pub fn strategy(&self) -> &VersionStrategy {
+        &self.strategy
+    }
+
+    pub fn is_default(&self, version: u32) -> bool {
+        self.default == Some(version)
+    }
+
+    /// Path prefix of the version, root for the strategies other than prefix
+    pub fn prefix(&self, version: u32) -> String {
+        match self.strategy {
+            VersionStrategy::Prefix => format!("/v{}", version),
+            _ => String::from("/"),
+        }
+    }
+
+    /// Guard matching the requests of the version
+    /// None for the prefix strategy as the path already selects the version
+    pub fn guard(&self, version: u32) -> Option<VersionGuard> {
+        match self.strategy {
+            VersionStrategy::Prefix => None,
+            _ => Some(VersionGuard {
+                strategy: self.strategy.clone(),
+                version,
+                is_default: self.is_default(version),
+            }),
+        }
+    }
+
+    /// Middleware adding the deprecation headers if the version is deprecated
+    pub fn deprecation(&self, version: u32) -> Option<Deprecation> {
+        self.deprecated.get(&version).map(|sunset| Deprecation {
+            sunset: sunset.clone(),
+        })
+    }
+}
+
+/// Guard created by `Versioning` for the header and media type strategies
+pub struct VersionGuard {
+    strategy: VersionStrategy,
+    version: u32,
+    is_default: bool,
+}
+
+impl Guard for VersionGuard {
+    fn check(&self, req: &Parts) -> bool {
+        let requested = match &self.strategy {
+            VersionStrategy::Prefix => return true,
+            VersionStrategy::Header(name) => req
+                .headers
+                .get(name)
+                .map(|value| value.to_str().ok().and_then(parse_version)),
+            VersionStrategy::MediaType(vendor) => req
+                .headers
+                .get_all(ACCEPT)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .find_map(|media_type| vendor_version(media_type, vendor)),
+        };
+
+        match requested {
+            Some(requested) => requested == Some(self.version),
+            None => self.is_default,
+        }
+    }
+
+    fn rejection(&self) -> StatusCode {
+        match self.strategy {
+            VersionStrategy::MediaType(_) => StatusCode::NOT_ACCEPTABLE,
+            _ => StatusCode::NOT_FOUND,
+        }
+    }
+}
+
+/// Middleware created by `Versioning` for the deprecated versions
+pub struct Deprecation {
+    sunset: Option<HeaderValue>,
+}
+
+impl Middleware for Deprecation {
+    fn handle<'a>(
+        &'a self,
+        context: Context,
+        ep_executor: EndpointExecutor<'a>,
+    ) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+        let sunset = self.sunset.clone();
+
+        Box::new(ep_executor.next(context).map(move |mut res| {
+            let headers = res.headers_mut();
+
+            headers.insert("deprecation", HeaderValue::from_static("true"));
+
+            if let Some(sunset) = sunset {
+                headers.insert("sunset", sunset);
+            }
+
+            res
+        }))
+    }
+}
+
+/// Parse `2` or `v2`, None if the version is malformed
+fn parse_version(version: &str) -> Option<u32> {
+    let version = version.trim();
+    let version = version
+        .strip_prefix('v')
+        .or_else(|| version.strip_prefix('V'))
+        .unwrap_or(version);
+
+    version.parse().ok()
+}
+
+/// Version of the vendor media type, for example `application/vnd.acme.v2+json`
+/// Outer None if the media type does not belong to the vendor
+fn vendor_version(media_type: &str, vendor: &str) -> Option<Option<u32>> {
+    let essence = media_type.split(';').next().unwrap_or("").trim();
+    let sub_type = essence.split('/').nth(1)?.to_ascii_lowercase();
+    let rest = sub_type
+        .strip_prefix("vnd.")?
+        .strip_prefix(vendor)?
+        .strip_prefix('.')?;
+    let version = rest.split('+').next().unwrap_or(rest);
+
+    Some(parse_version(version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Request;
+
+    fn parts(headers: &[(&str, &str)]) -> Parts {
+        let mut req_builder = Request::builder();
+
+        for (name, value) in headers.iter() {
+            req_builder.header(*name, *value);
+        }
+
+        req_builder.body(Body::empty()).unwrap().into_parts().0
+    }
+
+    #[test]
+    fn version_header_guard_test() {
+        let versioning = Versioning::new(VersionStrategy::header("api-version")).default_version(1);
+        let v1 = versioning.guard(1).unwrap();
+        let v2 = versioning.guard(2).unwrap();
+
+        assert!(v1.check(&parts(&[])));
+        assert!(!v2.check(&parts(&[])));
+        assert!(v2.check(&parts(&[("api-version", "2")])));
+        assert!(v2.check(&parts(&[("api-version", "v2")])));
+        assert!(!v1.check(&parts(&[("api-version", "2")])));
+        assert!(!v1.check(&parts(&[("api-version", "latest")])));
+    }
+
+    #[test]
+    fn version_media_type_guard_test() {
+        let versioning = Versioning::new(VersionStrategy::media_type("acme")).default_version(1);
+        let v1 = versioning.guard(1).unwrap();
+        let v2 = versioning.guard(2).unwrap();
+
+        assert!(v1.check(&parts(&[("accept", "application/json")])));
+        assert!(v2.check(&parts(&[(
+            "accept",
+            "text/html, application/vnd.acme.v2+json; q=0.9"
+        )])));
+        assert!(!v1.check(&parts(&[("accept", "application/vnd.acme.v2+json")])));
+        assert!(!v2.check(&parts(&[("accept", "application/vnd.other.v2+json")])));
+        assert_eq!(v2.rejection(), StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[test]
+    fn version_prefix_test() {
+        let versioning = Versioning::new(VersionStrategy::Prefix);
+
+        assert_eq!(versioning.prefix(3), "/v3");
+        assert!(versioning.guard(3).is_none());
+        assert!(versioning.deprecation(3).is_none());
+    }
+}